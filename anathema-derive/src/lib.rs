@@ -0,0 +1,288 @@
+//! Derive macros for anathema.
+//!
+//! Use the re-export `anathema::templates::ViewModel` (requires the `derive` feature)
+//! rather than depending on this crate directly.
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Field, Fields, Ident, LitStr, Path, Result, Type};
+
+/// Derive `anathema::templates::ViewModel` for a struct with named fields.
+///
+/// Every field becomes a value in the data context, with the field name as the key.
+///
+/// If the struct has a `DataCtx` field a setter is generated for every field,
+/// that updates both the field and the data context:
+///
+/// * `set_<field>(value)` replaces the value.
+/// * `update_<field>(|value| ..)` for a nested view model, i.e. a field marked with `#[view_model(nested)]`.
+/// * `sync()` inserts every field, e.g. to populate the data context the first time.
+///
+/// A struct without a data context gets `From<Struct> for Value` instead,
+/// so it can be used in a list, or any other field, of another view model.
+///
+/// Field attributes:
+///
+/// * `#[view_model(skip)]`: the field is not part of the data context.
+/// * `#[view_model(nested)]`: the field is a view model itself, stored as a map.
+/// * `#[view_model(rename = "name")]`: use a different key in the data context.
+/// * `#[view_model(data)]`: the data context, if the type is not called `DataCtx`.
+///
+/// To implement `UserModel` as well, pass the message type and a method to handle events:
+///
+/// ```
+/// use anathema::runtime::Event;
+/// use anathema::templates::{DataCtx, ViewModel};
+/// use anathema::widgets::WidgetContainer;
+///
+/// #[derive(ViewModel)]
+/// #[view_model(message = String, event = Self::on_event)]
+/// struct Model {
+///     counter: u64,
+///     data: DataCtx,
+/// }
+///
+/// impl Model {
+///     fn on_event(&mut self, event: Event<String>, root: &mut WidgetContainer) {
+///         self.set_counter(self.counter + 1);
+///     }
+/// }
+///
+/// let mut model = Model { counter: 0, data: DataCtx::empty() };
+/// model.set_counter(1u64);
+/// assert_eq!(model.data.get_u64("counter"), Some(&1));
+/// ```
+#[proc_macro_derive(ViewModel, attributes(view_model))]
+pub fn derive_view_model(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    view_model(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+// -----------------------------------------------------------------------------
+//     - Attributes -
+// -----------------------------------------------------------------------------
+#[derive(Default)]
+struct ModelAttributes {
+    message: Option<Type>,
+    event: Option<Path>,
+}
+
+impl ModelAttributes {
+    fn parse(input: &DeriveInput) -> Result<Self> {
+        let mut attributes = Self::default();
+        for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("view_model")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("message") {
+                    attributes.message = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("event") {
+                    attributes.event = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `message` or `event`"))
+                }
+            })?;
+        }
+        Ok(attributes)
+    }
+}
+
+#[derive(Default)]
+struct FieldAttributes {
+    skip: bool,
+    nested: bool,
+    data: bool,
+    rename: Option<String>,
+}
+
+impl FieldAttributes {
+    fn parse(field: &Field) -> Result<Self> {
+        let mut attributes = Self::default();
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("view_model")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    attributes.skip = true;
+                } else if meta.path.is_ident("nested") {
+                    attributes.nested = true;
+                } else if meta.path.is_ident("data") {
+                    attributes.data = true;
+                } else if meta.path.is_ident("rename") {
+                    attributes.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else {
+                    return Err(meta.error("expected `skip`, `nested`, `data` or `rename`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        // A field of type `DataCtx` is the data context, unless it's explicitly skipped
+        if !attributes.skip && is_data_ctx(&field.ty) {
+            attributes.data = true;
+        }
+
+        Ok(attributes)
+    }
+}
+
+fn is_data_ctx(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident == "DataCtx").unwrap_or(false),
+        _ => false,
+    }
+}
+
+// -----------------------------------------------------------------------------
+//     - View model -
+// -----------------------------------------------------------------------------
+struct ModelField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    key: String,
+    nested: bool,
+}
+
+impl ModelField<'_> {
+    // The value of the field
+    fn value(&self) -> TokenStream2 {
+        let ident = self.ident;
+        match self.nested {
+            true => quote!(::anathema::templates::ViewModel::to_value(&self.#ident)),
+            false => quote!(::anathema::widgets::Value::from(::std::clone::Clone::clone(&self.#ident))),
+        }
+    }
+
+    fn setter(&self, data: &Ident) -> TokenStream2 {
+        let Self { ident, ty, key, .. } = self;
+        let value = self.value();
+
+        match self.nested {
+            true => {
+                let name = format_ident!("update_{}", ident);
+                let doc = format!("Update `{ident}` and the data context.");
+                quote! {
+                    #[doc = #doc]
+                    pub fn #name(&mut self, f: impl ::std::ops::FnOnce(&mut #ty)) {
+                        f(&mut self.#ident);
+                        self.#data.insert(#key, #value);
+                    }
+                }
+            }
+            false => {
+                let name = format_ident!("set_{}", ident);
+                let doc = format!("Set `{ident}` and update the data context.");
+                quote! {
+                    #[doc = #doc]
+                    pub fn #name(&mut self, value: impl ::std::convert::Into<#ty>) {
+                        self.#ident = value.into();
+                        self.#data.insert(#key, #value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn view_model(input: DeriveInput) -> Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(&input.ident, "a view model needs named fields")),
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "a view model has to be a struct")),
+    };
+
+    let mut data = None;
+    let mut model_fields = vec![];
+    for field in fields {
+        let attributes = FieldAttributes::parse(field)?;
+        let ident = field.ident.as_ref().expect("named fields");
+
+        if attributes.data {
+            if data.is_some() {
+                return Err(Error::new_spanned(field, "a view model can only have one data context"));
+            }
+            data = Some(ident);
+            continue;
+        }
+
+        if attributes.skip {
+            continue;
+        }
+
+        let key = attributes.rename.unwrap_or_else(|| ident.to_string());
+        model_fields.push(ModelField { ident, ty: &field.ty, key, nested: attributes.nested });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let keys = model_fields.iter().map(|field| &field.key);
+    let values = model_fields.iter().map(ModelField::value);
+    let view_model = quote! {
+        impl #impl_generics ::anathema::templates::ViewModel for #name #ty_generics #where_clause {
+            fn to_value(&self) -> ::anathema::widgets::Value {
+                let mut map = ::std::collections::HashMap::new();
+                #(map.insert(::std::string::String::from(#keys), #values);)*
+                ::anathema::widgets::Value::Map(map)
+            }
+        }
+    };
+
+    let setters = match data {
+        Some(data) => {
+            let setters = model_fields.iter().map(|field| field.setter(data));
+            quote! {
+                impl #impl_generics #name #ty_generics #where_clause {
+                    #(#setters)*
+
+                    /// Insert every field into the data context.
+                    pub fn sync(&mut self) {
+                        let value = ::anathema::templates::ViewModel::to_value(self);
+                        if let ::anathema::widgets::Value::Map(map) = value {
+                            self.#data.insert_map(map);
+                        }
+                    }
+                }
+            }
+        }
+        None => quote! {
+            impl #impl_generics ::std::convert::From<#name #ty_generics> for ::anathema::widgets::Value #where_clause {
+                fn from(model: #name #ty_generics) -> Self {
+                    ::anathema::templates::ViewModel::to_value(&model)
+                }
+            }
+        },
+    };
+
+    let attributes = ModelAttributes::parse(&input)?;
+    let user_model = match (attributes.message, attributes.event, data) {
+        (Some(message), Some(event), Some(data)) => quote! {
+            impl #impl_generics ::anathema::runtime::UserModel for #name #ty_generics #where_clause {
+                type Message = #message;
+
+                fn event(
+                    &mut self,
+                    event: ::anathema::runtime::Event<Self::Message>,
+                    root: &mut ::anathema::widgets::WidgetContainer,
+                ) {
+                    #event(self, event, root)
+                }
+
+                fn data(&mut self) -> &mut ::anathema::templates::DataCtx {
+                    &mut self.#data
+                }
+            }
+        },
+        (None, None, _) => quote!(),
+        (_, _, None) => {
+            return Err(Error::new(Span::call_site(), "a user model needs a data context field"));
+        }
+        _ => return Err(Error::new(Span::call_site(), "a user model needs both `message` and `event`")),
+    };
+
+    Ok(quote! {
+        #view_model
+        #setters
+        #user_model
+    })
+}
@@ -1,6 +1,7 @@
 use super::AntString;
 
 /// Checks if a [`AntString`] contains either a [`char`] or a slice of chars.
+#[allow(dead_code)]
 pub trait Contains<P>: super::sealed::Sealed {
     /// Does the string contain the pattern?
     fn contains(&self, pat: P) -> bool;
@@ -5,6 +5,7 @@ pub trait Find<P>: super::sealed::Sealed {
     /// Find the pattern inside the string, starting from the beginning of the string
     fn find(&self, pat: P) -> Option<usize>;
     /// Find the pattern inside the string, starting from the end of the string
+    #[allow(dead_code)]
     fn rfind(&self, pat: P) -> Option<usize>;
 }
 
@@ -17,8 +17,8 @@
 //! assert!(string.contains('3'));
 //! assert_eq!(string.find('3').unwrap(), 3);
 //!
-//! for (color, c) in string.annotated_chars() {
-//!     eprintln!("{c} [{color:?}]");
+//! for (color, grapheme) in string.annotated_graphemes() {
+//!     eprintln!("{grapheme} [{color:?}]");
 //! }
 //!
 //! ```
@@ -31,6 +31,7 @@ use std::str::Bytes as StdBytes;
 use std::str::CharIndices as StdCharIndices;
 use std::str::Chars as StdChars;
 
+use unicode_segmentation::{GraphemeIndices as StdGraphemeIndices, Graphemes as StdGraphemes, UnicodeSegmentation};
 use unicode_width::UnicodeWidthStr;
 
 mod contains;
@@ -40,6 +41,7 @@ mod sealed;
 
 use fromrange::FromRange;
 
+#[allow(unused_imports)]
 pub use contains::Contains;
 pub use find::Find;
 
@@ -106,6 +108,13 @@ impl<'a, T> AntString<'a, T> {
         CharIndices::new(self.inner.iter().map(|(_annotation, slice)| *slice))
     }
 
+    /// An iterator over the grapheme clusters and their index (byte position) of the inner string slices.
+    /// A cluster never spans two slices.
+    #[must_use]
+    pub fn grapheme_indices(&'a self) -> GraphemeIndices<'a, impl Iterator<Item = &'a str>> {
+        GraphemeIndices::new(self.inner.iter().map(|(_annotation, slice)| *slice))
+    }
+
     /// Get a substring
     pub fn get(&self, range: impl FromRange) -> Self {
         let mut substring = Self { inner: self.inner.clone() };
@@ -205,7 +214,7 @@ impl<'a, T> AntString<'a, T> {
         let mut byte_index = 0;
 
         // If the first character is not a whitespace character then bail
-        if !self.inner.get(0).and_then(|(_, s)| s.chars().next()).unwrap_or(' ').is_whitespace() {
+        if !self.inner.first().and_then(|(_, s)| s.chars().next()).unwrap_or(' ').is_whitespace() {
             return;
         }
 
@@ -308,7 +317,7 @@ impl<'a, T> AntString<'a, T> {
             return None;
         }
 
-        let last_char = self.inner.iter().rev().next().and_then(|(_, s)| s.chars().last())?;
+        let last_char = self.inner.iter().next_back().and_then(|(_, s)| s.chars().last())?;
         let remove = last_char.len_utf8();
         let to = self.len() - remove;
         self.truncate(..to);
@@ -333,10 +342,11 @@ impl<'a, T> AntString<'a, T> {
 }
 
 impl<'a, T> AntString<'a, T> {
-    /// An iterator over the characters of the inner string slices
+    /// An iterator over the grapheme clusters of the inner string slices.
+    /// A cluster never spans two slices.
     #[must_use]
-    pub fn annotated_chars(&'a self) -> AnnotatedChars<'a, impl Iterator<Item = &'a (&'a T, &'a str)>, T> {
-        AnnotatedChars::new(self.inner.iter())
+    pub fn annotated_graphemes(&'a self) -> AnnotatedGraphemes<'a, impl Iterator<Item = &'a (&'a T, &'a str)>, T> {
+        AnnotatedGraphemes::new(self.inner.iter())
     }
 }
 
@@ -378,41 +388,39 @@ impl<'a, T> UnicodeWidthStr for AntString<'a, T> {
 }
 
 // -----------------------------------------------------------------------------
-//     - Annotated chars -
+//     - Annotated graphemes -
 // -----------------------------------------------------------------------------
-/// An iterator over annotated characters.
-pub struct AnnotatedChars<'a, T, U>
+/// An iterator over annotated grapheme clusters.
+pub struct AnnotatedGraphemes<'a, T, U>
 where
     T: Iterator<Item = &'a (&'a U, &'a str)>,
 {
     inner: T,
-    current: Option<(&'a U, StdChars<'a>)>,
+    current: Option<(&'a U, StdGraphemes<'a>)>,
 }
 
-impl<'a, T, U> AnnotatedChars<'a, T, U>
+impl<'a, T, U> AnnotatedGraphemes<'a, T, U>
 where
     T: Iterator<Item = &'a (&'a U, &'a str)>,
 {
     fn new(mut inner: T) -> Self {
-        let current = inner.next().map(|(annotation, slice)| (*annotation, slice.chars()));
+        let current = inner.next().map(|(annotation, slice)| (*annotation, slice.graphemes(true)));
         Self { inner, current }
     }
 }
 
-impl<'a, T, U> Iterator for AnnotatedChars<'a, T, U>
+impl<'a, T, U> Iterator for AnnotatedGraphemes<'a, T, U>
 where
     T: Iterator<Item = &'a (&'a U, &'a str)>,
 {
-    type Item = (&'a U, char);
+    type Item = (&'a U, &'a str);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (annotation, current) = self.current.as_mut()?;
-        match current.next() {
-            Some(s) => Some((annotation, s)),
-            None => {
-                self.current = self.inner.next().map(|(annotation, s)| (*annotation, s.chars()));
-                let (annotation, current) = self.current.as_mut()?;
-                current.next().map(|c| (*annotation, c))
+        loop {
+            let (annotation, current) = self.current.as_mut()?;
+            match current.next() {
+                Some(grapheme) => return Some((annotation, grapheme)),
+                None => self.current = self.inner.next().map(|(annotation, s)| (*annotation, s.graphemes(true))),
             }
         }
     }
@@ -422,6 +430,7 @@ where
 //     - Bytes -
 // -----------------------------------------------------------------------------
 /// An iterator over the bytes of the [`AntString`]
+#[allow(dead_code)]
 pub struct Bytes<'a, T: Iterator<Item = &'a str>> {
     inner: T,
     current: Option<StdBytes<'a>>,
@@ -454,6 +463,7 @@ impl<'a, T: Iterator<Item = &'a str>> Iterator for Bytes<'a, T> {
 //     - Chars -
 // -----------------------------------------------------------------------------
 /// An iterator over the chars of the [`AntString`]
+#[allow(dead_code)]
 pub struct Chars<'a, T: Iterator<Item = &'a str>> {
     inner: T,
     current: Option<StdChars<'a>>,
@@ -545,6 +555,40 @@ impl<'a, T: Iterator<Item = &'a str>> Iterator for CharIndices<'a, T> {
     }
 }
 
+// -----------------------------------------------------------------------------
+//     - Grapheme indices -
+// -----------------------------------------------------------------------------
+/// An iterator over the grapheme clusters and their index of the [`AntString`]
+pub struct GraphemeIndices<'a, T> {
+    inner: T,
+    current: Option<(usize, StdGraphemeIndices<'a>)>,
+    offset: usize,
+}
+
+impl<'a, T: Iterator<Item = &'a str>> GraphemeIndices<'a, T> {
+    fn new(mut inner: T) -> Self {
+        let current = inner.next().map(|slice| (slice.len(), slice.grapheme_indices(true)));
+        Self { inner, current, offset: 0 }
+    }
+}
+
+impl<'a, T: Iterator<Item = &'a str>> Iterator for GraphemeIndices<'a, T> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (next_offset, current) = self.current.as_mut()?;
+            match current.next() {
+                Some((i, grapheme)) => return Some((i + self.offset, grapheme)),
+                None => {
+                    self.offset += *next_offset;
+                    self.current = self.inner.next().map(|slice| (slice.len(), slice.grapheme_indices(true)));
+                }
+            }
+        }
+    }
+}
+
 // -----------------------------------------------------------------------------
 //     - Lines -
 // -----------------------------------------------------------------------------
@@ -595,7 +639,7 @@ mod test {
     #[test]
     fn bytes() {
         let s = [(&(), "a"), (&(), "b")];
-        let string = AntString::with_annotations(&s);
+        let string = AntString::with_annotations(s);
         let mut bytes = string.bytes();
         assert_eq!(bytes.next().unwrap(), b'a');
         assert_eq!(bytes.next().unwrap(), b'b');
@@ -605,7 +649,7 @@ mod test {
         #[test]
         // fn bytes_prop(left in "\\PC*", right in "\\PC*") {
         fn bytes_prop(left in any::<String>(), right in any::<String>()) {
-            let string = AntString::new(&[left.as_ref(), right.as_ref()]);
+            let string = AntString::new([left.as_ref(), right.as_ref()]);
 
             for (a, b) in left.bytes().chain(right.bytes()).zip(string.bytes()) {
                 eprintln!("{a} - {b}");
@@ -618,7 +662,7 @@ mod test {
     #[test]
     fn chars() {
         let s = [(&(), "a"), (&(), "b")];
-        let string = AntString::with_annotations(&s);
+        let string = AntString::with_annotations(s);
         let mut chars = string.chars();
         assert_eq!(chars.next().unwrap(), 'a');
         assert_eq!(chars.next().unwrap(), 'b');
@@ -627,7 +671,7 @@ mod test {
     proptest! {
         #[test]
         fn chars_prop(left in any::<String>(), right in any::<String>()) {
-            let string = AntString::new(&[left.as_ref(), right.as_ref()]);
+            let string = AntString::new([left.as_ref(), right.as_ref()]);
 
             for (a, b) in left.chars().chain(right.chars()).zip(string.chars()) {
                 prop_assert_eq!(a, b);
@@ -639,7 +683,7 @@ mod test {
     #[test]
     fn chars_in_reverse() {
         let s = [(&(), "a"), (&(), "bc")];
-        let string = AntString::with_annotations(&s);
+        let string = AntString::with_annotations(s);
         let mut chars = string.chars_rev();
         assert_eq!(chars.next().unwrap(), 'c');
         assert_eq!(chars.next().unwrap(), 'b');
@@ -649,7 +693,7 @@ mod test {
     #[test]
     fn char_indices() {
         let s = [(&(), "a"), (&(), "🍅"), (&(), "b")];
-        let string = AntString::with_annotations(&s);
+        let string = AntString::with_annotations(s);
         let mut chars = string.char_indices();
         assert_eq!(chars.next().unwrap(), (0, 'a'));
         assert_eq!(chars.next().unwrap(), (1, '🍅'));
@@ -659,7 +703,7 @@ mod test {
     proptest! {
         #[test]
         fn char_indices_prop(left in any::<String>()) {
-            let string = AntString::new(&[left.as_ref()]);
+            let string = AntString::new([left.as_ref()]);
 
             for (a, b) in left.char_indices().zip(string.char_indices()) {
                 prop_assert_eq!(a, b);
@@ -668,10 +712,26 @@ mod test {
         }
     }
 
+    #[test]
+    fn grapheme_indices() {
+        let s = [(&(), "e\u{301}"), (&(), "🇸🇪"), (&(), ""), (&(), "b")];
+        let string = AntString::with_annotations(s);
+        let graphemes = string.grapheme_indices().collect::<Vec<_>>();
+        assert_eq!(graphemes, vec![(0, "e\u{301}"), (3, "🇸🇪"), (11, "b")]);
+    }
+
+    #[test]
+    fn annotated_graphemes() {
+        let s = [(&1, "ae\u{301}"), (&2, "👩‍👩‍👧‍👦")];
+        let string = AntString::with_annotations(s);
+        let graphemes = string.annotated_graphemes().collect::<Vec<_>>();
+        assert_eq!(graphemes, vec![(&1, "a"), (&1, "e\u{301}"), (&2, "👩‍👩‍👧‍👦")]);
+    }
+
     #[test]
     fn collect() {
         let s = [(&(), "a"), (&(), "b")];
-        let string = AntString::with_annotations(&s);
+        let string = AntString::with_annotations(s);
         let chars = string.chars();
         let actual = chars.collect::<String>();
         let expected = "ab".to_string();
@@ -737,7 +797,7 @@ mod test {
     #[test]
     fn trim_start() {
         let s = ["   ", "  ", "a"];
-        let mut string = AntString::new(&s);
+        let mut string = AntString::new(s);
         string.trim_start();
         let actual = string.to_string();
         let expected = "a".to_string();
@@ -753,7 +813,7 @@ mod test {
     #[test]
     fn trim_end() {
         let s = ["a    ", " ", "  "];
-        let mut string = AntString::new(&s);
+        let mut string = AntString::new(s);
         string.trim_end();
         let actual = format!("{}", string);
         let expected = "a".to_string();
@@ -763,7 +823,7 @@ mod test {
     #[test]
     fn trim() {
         let s = ["  ", " ", "  a    ", " ", "  "];
-        let mut string = AntString::new(&s);
+        let mut string = AntString::new(s);
         string.trim();
         let actual = format!("{}", string);
         let expected = "a".to_string();
@@ -784,13 +844,13 @@ mod test {
     #[test]
     fn pop() {
         let s = ["0", "1", "2"];
-        let mut string = AntString::new(&s);
+        let mut string = AntString::new(s);
         let actual = string.pop().unwrap();
         let expected = '2';
         assert_eq!(expected, actual);
 
         let s = [" ", " "];
-        let mut string = AntString::new(&s);
+        let mut string = AntString::new(s);
         string.pop();
         let _actual = string.pop().unwrap();
         let _expected = ' ';
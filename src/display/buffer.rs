@@ -1,32 +1,50 @@
 #![deny(missing_docs)]
 use std::io::{Result, Write};
 
+use super::color::{mix, to_rgb};
+use super::grapheme::Grapheme;
+use super::{Color, Style};
+use super::{ScreenPos, Size};
 use crossterm::style::Print;
 use crossterm::{cursor, QueueableCommand};
-use unicode_width::UnicodeWidthChar;
 
-use super::Style;
-use super::{ScreenPos, Size};
+/// The index of a hyperlink in the [`Buffer`]s list of links.
+pub(crate) type LinkId = u32;
+
+// Open a hyperlink (OSC 8). The id lets the terminal treat cells on
+// different lines as the same link.
+fn open_link(w: &mut impl Write, id: LinkId, url: &str) -> Result<()> {
+    // Control characters would end the sequence early
+    let url = url.chars().filter(|c| !c.is_control()).collect::<String>();
+    w.queue(Print(format!("\x1b]8;id=anathema-{id};{url}\x1b\\")))?;
+    Ok(())
+}
+
+fn close_link(w: &mut impl Write) -> Result<()> {
+    w.queue(Print("\x1b]8;;\x1b\\"))?;
+    Ok(())
+}
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub(crate) struct Cell {
     pub(crate) style: Style,
     pub(crate) inner: CellState,
+    pub(crate) link: Option<LinkId>,
 }
 
 impl Cell {
     pub(crate) fn empty() -> Self {
         // It's important to reset the colours as there
         // might be residual colors from previous draw.
-        Self { style: Style::reset(), inner: CellState::Empty }
+        Self { style: Style::reset(), inner: CellState::Empty, link: None }
     }
 
-    fn continuation(style: Style) -> Self {
-        Self { style, inner: CellState::Continuation }
+    fn continuation(style: Style, link: Option<LinkId>) -> Self {
+        Self { style, inner: CellState::Continuation, link }
     }
 
-    pub(crate) fn new(c: char, style: Style) -> Self {
-        Self { style, inner: CellState::Occupied(c) }
+    pub(crate) fn new(grapheme: impl Into<Grapheme>, style: Style) -> Self {
+        Self { style, inner: CellState::Occupied(grapheme.into()), link: None }
     }
 }
 
@@ -35,8 +53,8 @@ impl Cell {
 pub(crate) enum CellState {
     /// Empty
     Empty,
-    /// Occupied by a certain character
-    Occupied(char),
+    /// Occupied by a grapheme cluster
+    Occupied(Grapheme),
     /// A continuation means this cell is part of another cell
     /// representing a value that spans more than two chars, e.g 💖
     Continuation,
@@ -50,17 +68,34 @@ pub(crate) enum CellState {
 ///
 /// The [`crate::Screen`] writes all the chars and their styles to the buffer, which works like a
 /// grid.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Buffer {
     size: Size,
     pub(crate) inner: Vec<Cell>,
+    // Hyperlinks referenced by cells.
+    // The links are dropped when the entire buffer is erased, so the ids
+    // are not stable between frames: compare links by url, not by id.
+    links: Vec<String>,
+}
+
+impl Clone for Buffer {
+    fn clone(&self) -> Self {
+        Self { size: self.size, inner: self.inner.clone(), links: self.links.clone() }
+    }
+
+    // Reuse the allocations, as the screen copies the buffer on every render
+    fn clone_from(&mut self, source: &Self) {
+        self.size = source.size;
+        self.inner.clone_from(&source.inner);
+        self.links.clone_from(&source.links);
+    }
 }
 
 impl Buffer {
     /// Crate a new `Buffer` with a given size.
     pub fn new(size: impl Into<Size>) -> Self {
         let size = size.into();
-        Self { inner: vec![Cell::empty(); (size.width * size.height) as usize], size }
+        Self { inner: vec![Cell::empty(); size.width * size.height], size, links: vec![] }
     }
 
     /// The size of the `Buffer`
@@ -71,6 +106,7 @@ impl Buffer {
     /// Resize the buffer, truncating what doesn't fit but keeps what does.
     pub fn resize(&mut self, size: Size) {
         let mut new_buf = Buffer::new(size);
+        new_buf.links = std::mem::take(&mut self.links);
         for (y, line) in self.cell_lines().enumerate() {
             if y >= size.height {
                 break;
@@ -88,6 +124,7 @@ impl Buffer {
 
         self.size = size;
         self.inner = new_buf.inner;
+        self.links = new_buf.links;
     }
 
     /// Put a character with a style at a given position.
@@ -96,12 +133,100 @@ impl Buffer {
         self.put(cell, pos);
     }
 
-    /// Get a `char` and [`Style`] at a given position inside the buffer.
-    pub fn get(&self, pos: ScreenPos) -> Option<(char, Style)> {
+    /// Put a grapheme cluster with a style at a given position.
+    ///
+    /// A zero width cluster (e.g a combining accent on its own) is appended to
+    /// the cluster in the previous cell, if there is one.
+    pub fn put_grapheme(&mut self, grapheme: &str, style: Style, pos: ScreenPos) {
+        let grapheme = Grapheme::new(grapheme);
+        if grapheme.width() == 0 && self.extend_previous(grapheme, pos) {
+            return;
+        }
+        self.put(Cell::new(grapheme, style), pos);
+    }
+
+    /// Put a grapheme cluster with a style at a given position, linking to `url`.
+    /// The link is rendered as a clickable hyperlink (OSC 8) in supported terminals.
+    pub fn put_link(&mut self, grapheme: &str, style: Style, url: &str, pos: ScreenPos) {
+        let link = match self.links.iter().position(|link| link == url) {
+            Some(index) => index,
+            None => {
+                self.links.push(url.to_string());
+                self.links.len() - 1
+            }
+        };
+
+        let mut cell = Cell::new(grapheme, style);
+        cell.link = Some(link as LinkId);
+        self.put(cell, pos);
+    }
+
+    /// Blend a background colour over the cell at a given position.
+    /// The `opacity` goes from `0.0` (transparent) to `1.0` (opaque).
+    ///
+    /// The grapheme in the cell is kept and its foreground is blended as well,
+    /// so what is below fades behind the colour.
+    ///
+    /// The colour of the terminal's own background is not known, so a cell without
+    /// a background colour (including an empty cell) is left as it is.
+    pub fn blend_background(&mut self, color: Color, opacity: f32, pos: ScreenPos) {
+        let index = self.index(pos);
+        let cell = &mut self.inner[index];
+
+        if opacity <= 0.0 || cell.style.bg.and_then(to_rgb).is_none() {
+            return;
+        }
+
+        cell.style.bg = Some(mix(cell.style.bg, color, opacity));
+        if let Some(fg) = cell.style.fg.filter(|fg| to_rgb(*fg).is_some()) {
+            cell.style.fg = Some(mix(Some(fg), color, opacity));
+        }
+    }
+
+    /// The hyperlink at a given position inside the buffer.
+    pub fn link(&self, pos: ScreenPos) -> Option<&str> {
+        let index = self.index(pos);
+        self.cell_link(self.inner.get(index)?)
+    }
+
+    fn cell_link(&self, cell: &Cell) -> Option<&str> {
+        self.links.get(cell.link? as usize).map(String::as_str)
+    }
+
+    /// Empty every cell.
+    /// As no cell refers to a link any more, the links are dropped as well.
+    pub fn erase(&mut self) {
+        self.inner.iter_mut().for_each(|cell| *cell = Cell::empty());
+        self.links.clear();
+    }
+
+    pub(crate) fn links(&self) -> &[String] {
+        &self.links
+    }
+
+    // Append a zero width cluster to the cluster before the given position.
+    // Returns false if there is no cluster to append to.
+    fn extend_previous(&mut self, grapheme: Grapheme, pos: ScreenPos) -> bool {
+        let row_start = self.index(ScreenPos::new(0, pos.y));
+        let index = self.index(pos).min(row_start + self.size.width);
+        let previous =
+            self.inner[row_start..index].iter_mut().rev().find(|cell| !matches!(cell.inner, CellState::Continuation));
+
+        match previous {
+            Some(Cell { inner: CellState::Occupied(current), .. }) => {
+                current.push_str(grapheme.as_str());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Get a grapheme cluster and [`Style`] at a given position inside the buffer.
+    pub fn get(&self, pos: ScreenPos) -> Option<(&str, Style)> {
         let index = self.index(pos);
         let cell = self.inner.get(index)?;
         match cell.inner {
-            CellState::Occupied(c) => Some((c, cell.style)),
+            CellState::Occupied(ref grapheme) => Some((grapheme.as_str(), cell.style)),
             _ => None,
         }
     }
@@ -113,40 +238,39 @@ impl Buffer {
     }
 
     /// An iterator over all the rows in the buffer
-    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = Option<(char, Style)>> + '_> {
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = Option<(&str, Style)>> + '_> {
         self.cell_lines().map(|chunk| {
             chunk.iter().map(|cell| match cell.inner {
-                CellState::Occupied(c) => Some((c, cell.style)),
+                CellState::Occupied(ref grapheme) => Some((grapheme.as_str(), cell.style)),
                 _ => None,
             })
         })
     }
 
     fn index(&self, pos: ScreenPos) -> usize {
-        pos.y as usize * self.size.width as usize + pos.x as usize
+        pos.y as usize * self.size.width + pos.x as usize
     }
 
     fn put(&mut self, mut cell: Cell, pos: ScreenPos) {
         let index = self.index(pos);
 
-        if let CellState::Occupied(c) = cell.inner {
-            // If this is a unicode char that is wider than one cell,
+        if let CellState::Occupied(grapheme) = cell.inner {
+            // If this is a grapheme that is wider than one cell,
             // add a continuation cell if it fits, this way if we overwrite it
             // we can set the continuation cell to `Empty`.
-            if pos.x < self.size.width as u16 {
-                if let Some(2..) = c.width() {
-                    self.put(Cell::continuation(cell.style), ScreenPos::new(pos.x + 1, pos.y));
-                }
+            if (pos.x as usize) + 1 < self.size.width && grapheme.width() >= 2 {
+                self.put(Cell::continuation(cell.style, cell.link), ScreenPos::new(pos.x + 1, pos.y));
             }
         }
 
-        let current = &mut self.inner[index as usize];
+        let current = &mut self.inner[index];
         cell.style.merge(current.style);
 
         match (&mut current.inner, cell.inner) {
             // Merge the styles
-            (CellState::Occupied(ref mut current_char), CellState::Occupied(new_char)) => {
-                *current_char = new_char;
+            (CellState::Occupied(ref mut current_grapheme), CellState::Occupied(new_grapheme)) => {
+                *current_grapheme = new_grapheme;
+                current.link = cell.link;
                 current.style.attributes |= cell.style.attributes;
 
                 if let Some(col) = cell.style.fg {
@@ -156,27 +280,32 @@ impl Buffer {
                 if let Some(col) = cell.style.bg {
                     current.style.bg = Some(col);
                 }
+
+                if let Some(col) = cell.style.underline_color {
+                    current.style.underline_color = Some(col);
+                }
             }
             _ => *current = cell,
         }
     }
 
-    fn cell_lines(&self) -> impl Iterator<Item = &[Cell]> {
-        self.inner.chunks(self.size.width as usize)
+    pub(crate) fn cell_lines(&self) -> impl Iterator<Item = &[Cell]> {
+        self.inner.chunks(self.size.width)
     }
 }
 
 #[cfg(test)]
 impl Buffer {
     fn cell_at(&self, x: usize, y: usize) -> Cell {
-        let index = y * self.size.width as usize + x;
-        *&self.inner[index]
+        let index = y * self.size.width + x;
+        self.inner[index]
     }
 
+    /// Get the character at a given position, panics if the cell is not occupied.
     pub fn char_at(&self, x: usize, y: usize) -> char {
         let cell = self.cell_at(x, y);
         match cell.inner {
-            CellState::Occupied(c) => c,
+            CellState::Occupied(grapheme) => grapheme.first(),
             _ => panic!("no character at index {x}, {y}"),
         }
     }
@@ -185,14 +314,14 @@ impl Buffer {
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub(crate) enum Change {
     Remove,
-    Insert(char),
+    Insert(Grapheme, Option<LinkId>),
 }
 
 impl Change {
     fn width(&self) -> usize {
         match self {
             Change::Remove => 1,
-            Change::Insert(c) => c.width().unwrap_or(1),
+            Change::Insert(grapheme, _) => grapheme.width().max(1),
         }
     }
 }
@@ -207,12 +336,14 @@ pub(crate) fn diff(old: &Buffer, new: &Buffer) -> Result<Vec<(ScreenPos, Option<
             let x = x as u16;
             let y = y as u16;
 
-            if old_cell == new_cell {
+            // The same link can have a different id in each buffer
+            let same_link = old.cell_link(old_cell) == new.cell_link(new_cell);
+            if old_cell.style == new_cell.style && old_cell.inner == new_cell.inner && same_link {
                 continue;
             }
 
             let style = match previous_style {
-                Some(previous) => (previous != new_cell.style).then(|| new_cell.style),
+                Some(previous) => (previous != new_cell.style).then_some(new_cell.style),
                 None => Some(new_cell.style),
             };
 
@@ -221,7 +352,7 @@ pub(crate) fn diff(old: &Buffer, new: &Buffer) -> Result<Vec<(ScreenPos, Option<
             let change = match new_cell.inner {
                 CellState::Empty => Change::Remove,
                 CellState::Continuation => continue,
-                CellState::Occupied(c) => Change::Insert(c),
+                CellState::Occupied(grapheme) => Change::Insert(grapheme, new_cell.link),
             };
 
             changes.push((ScreenPos::new(x, y), style, change));
@@ -234,36 +365,118 @@ pub(crate) fn diff(old: &Buffer, new: &Buffer) -> Result<Vec<(ScreenPos, Option<
 // -----------------------------------------------------------------------------
 //     - Draw changes -
 // -----------------------------------------------------------------------------
-pub(crate) fn draw_changes(mut w: impl Write, changes: Vec<(ScreenPos, Option<Style>, Change)>) -> Result<()> {
-    let mut last_y = None;
-    let mut next_cell_x = None;
+// Move the cursor relative to its current position.
+// This is used when rendering inline, as the absolute position of the
+// rendered region is not known (and changes if the terminal scrolls).
+fn move_relative(w: &mut impl Write, from: ScreenPos, to: ScreenPos) -> Result<()> {
+    // A zero movement would move the cursor by one
+    if to.y > from.y {
+        w.queue(cursor::MoveDown(to.y - from.y))?;
+    } else if to.y < from.y {
+        w.queue(cursor::MoveUp(from.y - to.y))?;
+    }
+    w.queue(cursor::MoveToColumn(to.x))?;
+    Ok(())
+}
+
+/// Draw the changes to the output.
+/// If `relative` is true the cursor is assumed to be at the top left of the buffer,
+/// and is moved back there once all the changes are drawn.
+///
+/// Adjacent changes on the same line are printed as a single string, and only
+/// the parts of a style that differ from the previous style are written.
+///
+/// Cells with a link are wrapped in a hyperlink, if the link is found in `links`.
+/// Pass an empty slice to draw links as plain text.
+pub(crate) fn draw_changes(
+    mut w: impl Write,
+    changes: Vec<(ScreenPos, Option<Style>, Change)>,
+    relative: bool,
+    links: &[String],
+) -> Result<()> {
+    let mut next_cell = None;
+    let mut cursor_pos = ScreenPos::ZERO;
+    // The style the output is currently using
+    let mut current_style: Option<Style> = None;
+    // Text waiting to be printed
+    let mut run = String::new();
+    // The currently open hyperlink
+    let mut current_link: Option<LinkId> = None;
 
     for (screen_pos, style, change) in changes {
         // Cursor movement
-        let should_move = match (last_y, next_cell_x) {
-            (Some(last_y), Some(next_x)) => screen_pos.y > last_y || next_x != screen_pos.x,
-            _ => true,
-        };
+        let should_move = next_cell != Some(screen_pos);
 
-        if should_move {
+        if should_move || style.is_some() {
+            print_run(&mut w, &mut run)?;
+        }
+
+        if should_move && relative {
+            move_relative(&mut w, cursor_pos, screen_pos)?;
+        } else if should_move {
             w.queue(cursor::MoveTo(screen_pos.x, screen_pos.y))?;
         }
 
-        last_y = Some(screen_pos.y);
-        next_cell_x = Some(screen_pos.x + change.width() as u16);
+        // Hyperlinks
+        let link = match change {
+            Change::Insert(_, Some(link)) if (link as usize) < links.len() => Some(link),
+            _ => None,
+        };
+
+        if link != current_link {
+            print_run(&mut w, &mut run)?;
+            if current_link.is_some() {
+                close_link(&mut w)?;
+            }
+            if let Some(link) = link {
+                open_link(&mut w, link, &links[link as usize])?;
+            }
+            current_link = link;
+        }
+
+        cursor_pos = ScreenPos::new(screen_pos.x + change.width() as u16, screen_pos.y);
+        next_cell = Some(cursor_pos);
 
         // Apply style
         if let Some(style) = style {
-            style.write(&mut w)?;
+            style.write_diff(current_style.as_ref(), &mut w)?;
+            current_style = Some(match current_style {
+                // Unset colours are left as they are, except for the underline colour
+                Some(current) => Style {
+                    fg: style.fg.or(current.fg),
+                    bg: style.bg.or(current.bg),
+                    underline_color: style.underline_color,
+                    attributes: style.attributes,
+                },
+                None => style,
+            });
         }
 
         // Draw changes
         match change {
-            Change::Insert(c) => w.queue(Print(c))?,
-            Change::Remove => w.queue(Print(' '))?,
+            Change::Insert(grapheme, _) => run.push_str(grapheme.as_str()),
+            Change::Remove => run.push(' '),
         };
     }
 
+    print_run(&mut w, &mut run)?;
+
+    if current_link.is_some() {
+        close_link(&mut w)?;
+    }
+
+    if relative {
+        move_relative(&mut w, cursor_pos, ScreenPos::ZERO)?;
+    }
+
+    Ok(())
+}
+
+fn print_run(w: &mut impl Write, run: &mut String) -> Result<()> {
+    if !run.is_empty() {
+        w.queue(Print(&run))?;
+        run.clear();
+    }
     Ok(())
 }
 
@@ -291,9 +504,70 @@ mod test {
         let (_, _, change_2) = changes[1]; // Remove 'V'
         let (_, _, change_3) = changes[2]; // Insert 'N'
 
-        assert_eq!(Change::Insert('C'), change_1);
+        assert_eq!(Change::Insert('C'.into(), None), change_1);
         assert_eq!(Change::Remove, change_2);
-        assert_eq!(Change::Insert('N'), change_3);
+        assert_eq!(Change::Insert('N'.into(), None), change_3);
+    }
+
+    #[test]
+    fn graphemes() {
+        let mut buffer = Buffer::new((4u16, 1));
+        buffer.put_grapheme("e\u{301}", Style::reset(), ScreenPos::new(0, 0));
+        buffer.put_grapheme("🇸🇪", Style::reset(), ScreenPos::new(1, 0));
+        // A lone combining accent is added to the previous cluster
+        buffer.put_grapheme("\u{301}", Style::reset(), ScreenPos::new(3, 0));
+
+        assert_eq!(buffer.get(ScreenPos::new(0, 0)).unwrap().0, "e\u{301}");
+        assert_eq!(buffer.get(ScreenPos::new(1, 0)).unwrap().0, "🇸🇪\u{301}");
+        assert_eq!(buffer.inner[2].inner, CellState::Continuation);
+        assert!(buffer.get(ScreenPos::new(3, 0)).is_none());
+    }
+
+    #[test]
+    fn wide_grapheme_in_last_column() {
+        let mut buffer = Buffer::new((2u16, 2));
+        buffer.put_grapheme("👩‍👩‍👧‍👦", Style::reset(), ScreenPos::new(1, 0));
+        assert_eq!(buffer.get(ScreenPos::new(1, 0)).unwrap().0, "👩‍👩‍👧‍👦");
+        // The continuation does not spill over onto the next line
+        assert_eq!(buffer.inner[2], Cell::empty());
+    }
+
+    #[test]
+    fn blend_background() {
+        let mut buffer = Buffer::new((2u16, 1));
+        let mut style = Style::new();
+        style.set_fg(Color::Rgb { r: 255, g: 255, b: 255 });
+        style.set_bg(Color::Rgb { r: 0, g: 0, b: 200 });
+        buffer.put_char('a', style, ScreenPos::new(0, 0));
+
+        let red = Color::Rgb { r: 200, g: 0, b: 0 };
+        buffer.blend_background(red, 0.5, ScreenPos::new(0, 0));
+        buffer.blend_background(red, 0.5, ScreenPos::new(1, 0));
+
+        // The char is kept and both colours are blended
+        let (c, style) = buffer.get(ScreenPos::new(0, 0)).unwrap();
+        assert_eq!(c, "a");
+        assert_eq!(style.bg, Some(Color::Rgb { r: 100, g: 0, b: 100 }));
+        assert_eq!(style.fg, Some(Color::Rgb { r: 228, g: 128, b: 128 }));
+
+        // An empty cell is left as it is
+        assert_eq!(buffer.inner[1], Cell::empty());
+    }
+
+    #[test]
+    fn blend_background_transparent() {
+        let mut buffer = Buffer::new((2u16, 1));
+        let mut style = Style::new();
+        style.set_bg(Color::Rgb { r: 0, g: 0, b: 200 });
+        buffer.put_char('a', style, ScreenPos::new(0, 0));
+        buffer.put_char('b', Style::new(), ScreenPos::new(1, 0));
+        let before = buffer.clone();
+
+        // Nothing changes at zero opacity, or without a background
+        let red = Color::Rgb { r: 200, g: 0, b: 0 };
+        buffer.blend_background(red, 0.0, ScreenPos::new(0, 0));
+        buffer.blend_background(red, 0.5, ScreenPos::new(1, 0));
+        assert_eq!(buffer.inner, before.inner);
     }
 
     #[test]
@@ -308,4 +582,67 @@ mod test {
         assert_eq!(buffer.inner[0], Cell::new('1', Style::reset()));
         assert_eq!(buffer.inner[1], Cell::new('3', Style::reset()));
     }
+
+    #[test]
+    fn draw_runs() {
+        let old_buffer = Buffer::new((6u16, 2));
+        let mut new_buffer = Buffer::new((6u16, 2));
+        let mut bold = Style::reset();
+        bold.set_bold(true);
+        new_buffer.put_char('a', Style::reset(), ScreenPos::new(0, 0));
+        new_buffer.put_char('b', Style::reset(), ScreenPos::new(1, 0));
+        new_buffer.put_char('c', bold, ScreenPos::new(2, 0));
+        new_buffer.put_char('d', bold, ScreenPos::new(3, 0));
+        new_buffer.put_char('e', bold, ScreenPos::new(0, 1));
+
+        let changes = diff(&old_buffer, &new_buffer).unwrap();
+        let mut output = vec![];
+        draw_changes(&mut output, changes, false, &[]).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        // Adjacent cells are printed together, and only the bold attribute is written
+        // when the style changes. The empty cells after "cd" are unchanged.
+        assert!(output.contains("ab\x1b[1mcd\x1b[2;1He"));
+        assert_eq!(output.matches("\x1b[1m").count(), 1);
+    }
+
+    #[test]
+    fn draw_links() {
+        let old_buffer = Buffer::new((4u16, 1));
+        let mut new_buffer = Buffer::new((4u16, 1));
+        new_buffer.put_char('a', Style::reset(), ScreenPos::new(0, 0));
+        new_buffer.put_link("b", Style::reset(), "https://example.com", ScreenPos::new(1, 0));
+        new_buffer.put_link("c", Style::reset(), "https://example.com", ScreenPos::new(2, 0));
+        new_buffer.put_char('d', Style::reset(), ScreenPos::new(3, 0));
+        assert_eq!(new_buffer.link(ScreenPos::new(2, 0)), Some("https://example.com"));
+
+        let changes = diff(&old_buffer, &new_buffer).unwrap();
+        let mut output = vec![];
+        draw_changes(&mut output, changes.clone(), false, new_buffer.links()).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with("a\x1b]8;id=anathema-0;https://example.com\x1b\\bc\x1b]8;;\x1b\\d"));
+
+        // Without links the text is plain
+        let mut output = vec![];
+        draw_changes(&mut output, changes, false, &[]).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with("abcd"));
+    }
+
+    #[test]
+    fn draw_relative() {
+        let old_buffer = Buffer::new((3u16, 2));
+        let mut new_buffer = Buffer::new((3u16, 2));
+        new_buffer.inner[4] = Cell::new('x', Style::reset());
+
+        let changes = diff(&old_buffer, &new_buffer).unwrap();
+        let mut output = vec![];
+        draw_changes(&mut output, changes, true, &[]).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        // Move down a line and to the second column, then back to the top left
+        assert!(output.starts_with("\x1b[1B\x1b[2G"));
+        assert!(output.ends_with("x\x1b[1A\x1b[1G"));
+        assert!(!output.contains('H'));
+    }
 }
@@ -0,0 +1,231 @@
+use std::env;
+use std::ffi::OsStr;
+
+use super::{Color, Style};
+
+// The RGB values of the 16 ANSI colours (xterm defaults).
+const ANSI_16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (128, 0, 0)),
+    (Color::DarkGreen, (0, 128, 0)),
+    (Color::DarkYellow, (128, 128, 0)),
+    (Color::DarkBlue, (0, 0, 128)),
+    (Color::DarkMagenta, (128, 0, 128)),
+    (Color::DarkCyan, (0, 128, 128)),
+    (Color::Grey, (192, 192, 192)),
+    (Color::DarkGrey, (128, 128, 128)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (0, 0, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// The intensity steps of the 6x6x6 colour cube in the 256 colour palette.
+const CUBE_STEPS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The number of colours the output can display.
+///
+/// Colours that the output can't display are converted to the
+/// closest colour it can display when rendering.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// 24 bit colour, all colours are written as is.
+    TrueColor,
+    /// The 256 colour palette.
+    Ansi256,
+    /// The 16 ANSI colours.
+    Ansi16,
+    /// No colours at all.
+    NoColor,
+}
+
+impl ColorDepth {
+    /// Detect the colour depth from the environment.
+    ///
+    /// * `NO_COLOR` set to anything but an empty string disables colours
+    /// * `COLORTERM` set to `truecolor` or `24bit` enables true colour
+    /// * `TERM` containing `256color` uses the 256 colour palette
+    /// * `TERM` set to `dumb` disables colours
+    ///
+    /// Anything else falls back to the 16 ANSI colours.
+    pub fn detect() -> Self {
+        Self::from_env(
+            env::var_os("NO_COLOR").as_deref(),
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    fn from_env(no_color: Option<&OsStr>, colorterm: Option<&str>, term: Option<&str>) -> Self {
+        // An empty `NO_COLOR` is the same as not setting it (see https://no-color.org)
+        if let Some(no_color) = no_color {
+            if !no_color.is_empty() {
+                return Self::NoColor;
+            }
+        }
+
+        if let Some("truecolor" | "24bit") = colorterm {
+            return Self::TrueColor;
+        }
+
+        match term {
+            Some("dumb") => Self::NoColor,
+            Some(term) if term.contains("256color") => Self::Ansi256,
+            _ => Self::Ansi16,
+        }
+    }
+
+    /// Convert a colour to the closest colour of this depth.
+    /// With [`ColorDepth::NoColor`] every colour is converted to `Color::Reset`.
+    pub fn convert(&self, color: Color) -> Color {
+        match (self, color) {
+            (_, Color::Reset) | (Self::TrueColor, _) => color,
+            (Self::NoColor, _) => Color::Reset,
+            (Self::Ansi256, Color::Rgb { r, g, b }) => Color::AnsiValue(rgb_to_ansi_256(r, g, b)),
+            (Self::Ansi256, _) => color,
+            (Self::Ansi16, Color::Rgb { r, g, b }) => rgb_to_ansi_16(r, g, b),
+            (Self::Ansi16, Color::AnsiValue(index)) => {
+                let (r, g, b) = ansi_256_to_rgb(index);
+                rgb_to_ansi_16(r, g, b)
+            }
+            (Self::Ansi16, _) => color,
+        }
+    }
+
+    pub(crate) fn apply(&self, mut style: Style) -> Style {
+        style.fg = style.fg.map(|c| self.convert(c));
+        style.bg = style.bg.map(|c| self.convert(c));
+        style.underline_color = style.underline_color.map(|c| self.convert(c));
+        style
+    }
+}
+
+/// The RGB value of a colour, or `None` for `Color::Reset`.
+pub(crate) fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb { r, g, b } => Some((r, g, b)),
+        Color::AnsiValue(index) => Some(ansi_256_to_rgb(index)),
+        named => ANSI_16.iter().find(|(c, _)| *c == named).map(|(_, rgb)| *rgb),
+    }
+}
+
+/// Mix two colours, `amount` is how much of `to` to use: `0.0` is `from`, `1.0` is `to`.
+///
+/// An unset (or reset) `from` colour is treated as black, as the terminal's
+/// own background colour is not known.
+/// `Color::Reset` can't be mixed so a reset `to` is returned as is.
+pub(crate) fn mix(from: Option<Color>, to: Color, amount: f32) -> Color {
+    let (r2, g2, b2) = match to_rgb(to) {
+        Some(rgb) => rgb,
+        None => return to,
+    };
+    let (r1, g1, b1) = from.and_then(to_rgb).unwrap_or((0, 0, 0));
+    let amount = amount.clamp(0.0, 1.0);
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+    Color::Rgb { r: channel(r1, r2), g: channel(g1, g2), b: channel(b1, b2) }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+fn rgb_to_ansi_16(r: u8, g: u8, b: u8) -> Color {
+    ANSI_16.iter().min_by_key(|(_, rgb)| distance(*rgb, (r, g, b))).map(|(c, _)| *c).expect("palette is not empty")
+}
+
+fn rgb_to_ansi_256(r: u8, g: u8, b: u8) -> u8 {
+    let closest_step = |v: u8| {
+        (0..CUBE_STEPS.len()).min_by_key(|i| (CUBE_STEPS[*i] as i32 - v as i32).abs()).expect("steps is not empty")
+    };
+
+    let (ri, gi, bi) = (closest_step(r), closest_step(g), closest_step(b));
+    let cube = (CUBE_STEPS[ri], CUBE_STEPS[gi], CUBE_STEPS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    // The grey ramp (232 - 255) goes from 8 to 238 in steps of 10
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let grey_index = ((avg.saturating_sub(3)) / 10).min(23) as u8;
+    let grey = 8 + grey_index * 10;
+
+    match distance((grey, grey, grey), (r, g, b)) < distance(cube, (r, g, b)) {
+        true => 232 + grey_index,
+        false => cube_index as u8,
+    }
+}
+
+fn ansi_256_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_16[index as usize].1,
+        16..=231 => {
+            let i = (index - 16) as usize;
+            (CUBE_STEPS[i / 36], CUBE_STEPS[(i / 6) % 6], CUBE_STEPS[i % 6])
+        }
+        _ => {
+            let grey = 8 + (index - 232) * 10;
+            (grey, grey, grey)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mix_colours() {
+        let white = Color::Rgb { r: 255, g: 255, b: 255 };
+        let red = Color::Rgb { r: 200, g: 0, b: 0 };
+        assert_eq!(mix(Some(white), red, 0.0), white);
+        assert_eq!(mix(Some(white), red, 1.0), red);
+        assert_eq!(mix(Some(white), red, 0.5), Color::Rgb { r: 228, g: 128, b: 128 });
+        // Unset is black
+        assert_eq!(mix(None, red, 0.5), Color::Rgb { r: 100, g: 0, b: 0 });
+        assert_eq!(mix(Some(Color::Reset), red, 0.5), Color::Rgb { r: 100, g: 0, b: 0 });
+        // Named colours are mixed by their RGB value
+        assert_eq!(mix(Some(Color::Black), Color::White, 1.0), Color::Rgb { r: 255, g: 255, b: 255 });
+        assert_eq!(mix(Some(white), Color::Reset, 0.5), Color::Reset);
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(ColorDepth::from_env(Some(OsStr::new("1")), Some("truecolor"), None), ColorDepth::NoColor);
+        assert_eq!(ColorDepth::from_env(Some(OsStr::new("")), Some("truecolor"), None), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, Some("24bit"), Some("xterm")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, None, Some("xterm-256color")), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env(None, None, Some("xterm")), ColorDepth::Ansi16);
+        assert_eq!(ColorDepth::from_env(None, None, Some("dumb")), ColorDepth::NoColor);
+    }
+
+    #[test]
+    fn quantize_256() {
+        let depth = ColorDepth::Ansi256;
+        assert_eq!(depth.convert(Color::Rgb { r: 255, g: 0, b: 0 }), Color::AnsiValue(196));
+        assert_eq!(depth.convert(Color::Rgb { r: 255, g: 135, b: 0 }), Color::AnsiValue(208));
+        assert_eq!(depth.convert(Color::Rgb { r: 128, g: 128, b: 128 }), Color::AnsiValue(244));
+        assert_eq!(depth.convert(Color::Red), Color::Red);
+    }
+
+    #[test]
+    fn quantize_16() {
+        let depth = ColorDepth::Ansi16;
+        assert_eq!(depth.convert(Color::Rgb { r: 250, g: 10, b: 10 }), Color::Red);
+        assert_eq!(depth.convert(Color::Rgb { r: 20, g: 20, b: 20 }), Color::Black);
+        assert_eq!(depth.convert(Color::AnsiValue(21)), Color::Blue);
+        assert_eq!(depth.convert(Color::AnsiValue(9)), Color::Red);
+    }
+
+    #[test]
+    fn strip_colour() {
+        let mut style = Style::new();
+        style.set_fg(Color::Red);
+        style.set_bg(Color::Rgb { r: 1, g: 2, b: 3 });
+        let style = ColorDepth::NoColor.apply(style);
+        assert_eq!(style.fg, Some(Color::Reset));
+        assert_eq!(style.bg, Some(Color::Reset));
+    }
+}
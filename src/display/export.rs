@@ -0,0 +1,376 @@
+use std::fmt::Write as _;
+
+use super::buffer::{Buffer, Cell, CellState, LinkId};
+use super::color::to_rgb;
+use super::{Attributes, Color, Style, UnderlineStyle};
+
+// Colours used for inverse text when no colour is set.
+const DEFAULT_FG: (u8, u8, u8) = (255, 255, 255);
+const DEFAULT_BG: (u8, u8, u8) = (0, 0, 0);
+
+// SVG cell size in pixels.
+const SVG_CELL_WIDTH: usize = 9;
+const SVG_CELL_HEIGHT: usize = 18;
+const SVG_FONT_SIZE: usize = 15;
+
+// A run of cells on the same row sharing the same style and link.
+struct Run {
+    style: Style,
+    link: Option<LinkId>,
+    text: String,
+    // First column of the run
+    col: usize,
+    // Number of columns, including continuation cells
+    width: usize,
+}
+
+fn cell_text(cell: &Cell) -> Option<&str> {
+    match cell.inner {
+        CellState::Empty => Some(" "),
+        CellState::Occupied(ref grapheme) => Some(grapheme.as_str()),
+        // The character in the previous cell covers this cell
+        CellState::Continuation => None,
+    }
+}
+
+fn runs(line: &[Cell]) -> Vec<Run> {
+    let mut runs: Vec<Run> = vec![];
+
+    for (col, cell) in line.iter().enumerate() {
+        let run = match runs.last_mut() {
+            Some(run) if run.style == cell.style && run.link == cell.link => run,
+            _ => {
+                runs.push(Run { style: cell.style, link: cell.link, text: String::new(), col, width: 0 });
+                runs.last_mut().expect("a run was just pushed")
+            }
+        };
+
+        run.width += 1;
+        run.text.extend(cell_text(cell));
+    }
+
+    runs
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+// Foreground and background colours, swapped if the style is inversed.
+fn colors(style: &Style) -> (Option<String>, Option<String>) {
+    let fg = style.fg.and_then(to_rgb);
+    let bg = style.bg.and_then(to_rgb);
+
+    match style.attributes.contains(Attributes::INVERSE) {
+        true => (Some(hex(bg.unwrap_or(DEFAULT_BG))), Some(hex(fg.unwrap_or(DEFAULT_FG)))),
+        false => (fg.map(hex), bg.map(hex)),
+    }
+}
+
+fn text_decoration(attributes: Attributes) -> Option<String> {
+    let decorations = [
+        (Attributes::UNDERLINES, "underline"),
+        (Attributes::OVERLINED, "overline"),
+        (Attributes::CROSSED_OUT, "line-through"),
+        // Browsers ignore `blink`, but it keeps the information in the export
+        (Attributes::BLINKS, "blink"),
+    ]
+    .iter()
+    .filter(|(attr, _)| attributes.intersects(*attr))
+    .map(|(_, decoration)| *decoration)
+    .collect::<Vec<_>>();
+
+    (!decorations.is_empty()).then(|| decorations.join(" "))
+}
+
+fn css(style: &Style) -> String {
+    let mut css = vec![];
+    let (fg, bg) = colors(style);
+
+    if let Some(fg) = fg {
+        css.push(format!("color:{fg}"));
+    }
+
+    if let Some(bg) = bg {
+        css.push(format!("background-color:{bg}"));
+    }
+
+    if style.attributes.contains(Attributes::BOLD) {
+        css.push("font-weight:bold".to_string());
+    }
+
+    if style.attributes.contains(Attributes::DIM) {
+        css.push("opacity:0.5".to_string());
+    }
+
+    if style.attributes.contains(Attributes::ITALIC) {
+        css.push("font-style:italic".to_string());
+    }
+
+    if let Some(decoration) = text_decoration(style.attributes) {
+        css.push(format!("text-decoration:{decoration}"));
+    }
+
+    let underline_style = match style.underline_style() {
+        Some(UnderlineStyle::Double) => Some("double"),
+        Some(UnderlineStyle::Curly) => Some("wavy"),
+        Some(UnderlineStyle::Dotted) => Some("dotted"),
+        Some(UnderlineStyle::Dashed) => Some("dashed"),
+        Some(UnderlineStyle::Single) | None => None,
+    };
+
+    if let Some(underline_style) = underline_style {
+        css.push(format!("text-decoration-style:{underline_style}"));
+    }
+
+    if let Some(color) = style.underline_color.and_then(to_rgb) {
+        css.push(format!("text-decoration-color:{}", hex(color)));
+    }
+
+    if style.attributes.contains(Attributes::HIDDEN) {
+        css.push("visibility:hidden".to_string());
+    }
+
+    css.join(";")
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Buffer {
+    /// Export the buffer as plain text, without any styles.
+    /// Trailing spaces are removed from every line.
+    pub fn to_plain_text(&self) -> String {
+        self.cell_lines()
+            .map(|line| line.iter().filter_map(cell_text).collect::<String>().trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Export the buffer as a string with ANSI escape sequences for the styles.
+    /// Every line ends with a reset.
+    pub fn to_ansi(&self) -> String {
+        let mut output = vec![];
+
+        for line in self.cell_lines() {
+            for run in runs(line) {
+                let mut style = run.style;
+                // Unset colours have to be reset, or the colour of the previous run would be used
+                style.fg = Some(style.fg.unwrap_or(Color::Reset));
+                style.bg = Some(style.bg.unwrap_or(Color::Reset));
+                style.write(&mut output).expect("writing to a Vec<u8> never fails");
+                output.extend(run.text.as_bytes());
+            }
+            output.extend(b"\x1b[0m\n");
+        }
+
+        String::from_utf8(output).expect("the buffer only contains valid chars")
+    }
+
+    /// Export the buffer as an HTML `<pre>` element, with a `<span>` for every run
+    /// of cells sharing the same style.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<pre class=\"anathema\">");
+
+        for (y, line) in self.cell_lines().enumerate() {
+            if y > 0 {
+                html.push('\n');
+            }
+
+            for run in runs(line) {
+                let mut text = escape(&run.text);
+
+                if let Some(url) = run.link.and_then(|link| self.links().get(link as usize)) {
+                    text = format!("<a href=\"{}\">{text}</a>", escape(url));
+                }
+
+                match css(&run.style) {
+                    css if css.is_empty() => html.push_str(&text),
+                    css => {
+                        let _ = write!(html, "<span style=\"{css}\">{text}</span>");
+                    }
+                }
+            }
+        }
+
+        html.push_str("</pre>");
+        html
+    }
+
+    /// Export the buffer as an SVG image, drawing every character on a monospace grid.
+    pub fn to_svg(&self) -> String {
+        let width = self.size().width * SVG_CELL_WIDTH;
+        let height = self.size().height * SVG_CELL_HEIGHT;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             font-family=\"monospace\" font-size=\"{SVG_FONT_SIZE}\">"
+        );
+        let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", hex(DEFAULT_BG));
+
+        for (y, line) in self.cell_lines().enumerate() {
+            let top = y * SVG_CELL_HEIGHT;
+            for run in runs(line) {
+                let x = run.col * SVG_CELL_WIDTH;
+                let run_width = run.width * SVG_CELL_WIDTH;
+                let (fg, bg) = colors(&run.style);
+
+                if let Some(bg) = bg {
+                    let _ = writeln!(
+                        svg,
+                        "<rect x=\"{x}\" y=\"{top}\" width=\"{run_width}\" height=\"{SVG_CELL_HEIGHT}\" fill=\"{bg}\"/>"
+                    );
+                }
+
+                if run.text.trim().is_empty() || run.style.attributes.contains(Attributes::HIDDEN) {
+                    continue;
+                }
+
+                let mut attributes = format!(" fill=\"{}\"", fg.unwrap_or_else(|| hex(DEFAULT_FG)));
+
+                if run.style.attributes.contains(Attributes::BOLD) {
+                    attributes.push_str(" font-weight=\"bold\"");
+                }
+
+                if run.style.attributes.contains(Attributes::DIM) {
+                    attributes.push_str(" opacity=\"0.5\"");
+                }
+
+                if run.style.attributes.contains(Attributes::ITALIC) {
+                    attributes.push_str(" font-style=\"italic\"");
+                }
+
+                if let Some(decoration) = text_decoration(run.style.attributes) {
+                    let _ = write!(attributes, " text-decoration=\"{decoration}\"");
+                }
+
+                // Text is placed on the baseline, which is roughly 3/4 down the cell
+                let baseline = top + SVG_CELL_HEIGHT * 3 / 4;
+                let _ = writeln!(
+                    svg,
+                    "<text x=\"{x}\" y=\"{baseline}\" textLength=\"{run_width}\" lengthAdjust=\"spacingAndGlyphs\" \
+                     xml:space=\"preserve\"{attributes}>{}</text>",
+                    escape(&run.text)
+                );
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::display::ScreenPos;
+
+    fn buffer() -> Buffer {
+        let mut buffer = Buffer::new((6u16, 2));
+        let mut style = Style::new();
+        style.set_fg(Color::Red);
+        style.set_bold(true);
+        buffer.put_char('h', style, ScreenPos::new(0, 0));
+        buffer.put_char('i', style, ScreenPos::new(1, 0));
+        buffer.put_char('💖', Style::new(), ScreenPos::new(2, 0));
+        buffer.put_char('<', Style::new(), ScreenPos::new(0, 1));
+        buffer
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(buffer().to_plain_text(), "hi💖\n<");
+    }
+
+    #[test]
+    fn ansi() {
+        let ansi = buffer().to_ansi();
+        assert!(ansi.contains("\x1b[38;5;9m"));
+        assert!(ansi.contains("\x1b[1m"));
+        assert!(ansi.contains("hi\x1b["));
+        assert_eq!(ansi.lines().count(), 2);
+    }
+
+    #[test]
+    fn html() {
+        let html = buffer().to_html();
+        assert!(html.starts_with("<pre class=\"anathema\"><span style=\"color:#ff0000;font-weight:bold\">hi</span>"));
+        assert!(html.contains("💖"));
+        assert!(html.contains("\n&lt;"));
+    }
+
+    #[test]
+    fn html_attributes() {
+        let mut buffer = Buffer::new((1u16, 1));
+        let mut style = Style::new();
+        style.set_inverse(true);
+        style.set_underlined(true);
+        style.set_crossed_out(true);
+        style.set_italic(true);
+        buffer.put_char('x', style, ScreenPos::ZERO);
+
+        let expected = "<pre class=\"anathema\"><span style=\"color:#000000;background-color:#ffffff;\
+                        font-style:italic;text-decoration:underline line-through\">x</span></pre>";
+        assert_eq!(buffer.to_html(), expected);
+    }
+
+    #[test]
+    fn html_every_attribute() {
+        for attribute in (0..16).filter_map(|bit| Attributes::from_bits(1 << bit)) {
+            let mut style = Style::new();
+            style.attributes = attribute;
+            assert!(!css(&style).is_empty(), "{attribute:?} has no css");
+        }
+
+        let mut style = Style::new();
+        style.set_blink(true);
+        style.set_rapid_blink(true);
+        assert_eq!(css(&style), "text-decoration:blink");
+    }
+
+    #[test]
+    fn html_link() {
+        let mut buffer = Buffer::new((3u16, 1));
+        buffer.put_link("a", Style::new(), "https://example.com/?a&b", ScreenPos::new(0, 0));
+        buffer.put_link("b", Style::new(), "https://example.com/?a&b", ScreenPos::new(1, 0));
+        buffer.put_char('c', Style::new(), ScreenPos::new(2, 0));
+        let expected = "<pre class=\"anathema\"><a href=\"https://example.com/?a&amp;b\">ab</a>c</pre>";
+        assert_eq!(buffer.to_html(), expected);
+    }
+
+    #[test]
+    fn html_underline_style() {
+        let mut buffer = Buffer::new((1u16, 1));
+        let mut style = Style::new();
+        style.set_underline_style(Some(UnderlineStyle::Curly));
+        style.set_underline_color(Color::Red);
+        buffer.put_char('x', style, ScreenPos::ZERO);
+
+        let expected = "<span style=\"text-decoration:underline;text-decoration-style:wavy;\
+                        text-decoration-color:#ff0000\">x</span>";
+        assert!(buffer.to_html().contains(expected));
+    }
+
+    #[test]
+    fn svg() {
+        let svg = buffer().to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"54\" height=\"36\""));
+        assert!(svg.contains("textLength=\"18\" lengthAdjust=\"spacingAndGlyphs\" xml:space=\"preserve\" fill=\"#ff0000\" font-weight=\"bold\">hi</text>"));
+        // The wide char covers two cells
+        assert!(svg.contains("<text x=\"18\" y=\"13\" textLength=\"36\""));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
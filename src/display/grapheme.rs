@@ -0,0 +1,98 @@
+use std::fmt;
+
+use unicode_width::UnicodeWidthStr;
+
+// Number of bytes a grapheme can hold.
+// This is enough for most emoji sequences, e.g 👩‍👩‍👧‍👦 is 25 bytes.
+const CAPACITY: usize = 31;
+
+/// A grapheme cluster (what the user perceives as a single character)
+/// stored inline, so a cell can be `Copy`.
+///
+/// Clusters longer than the capacity are truncated to the characters that fit.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Grapheme {
+    len: u8,
+    bytes: [u8; CAPACITY],
+}
+
+impl Grapheme {
+    pub(crate) fn new(s: &str) -> Self {
+        let mut inst = Self { len: 0, bytes: [0; CAPACITY] };
+        inst.push_str(s);
+        inst
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len as usize]).expect("only whole chars are stored")
+    }
+
+    /// The first char of the cluster
+    #[cfg(test)]
+    pub(crate) fn first(&self) -> char {
+        self.as_str().chars().next().unwrap_or(' ')
+    }
+
+    /// The number of cells the grapheme covers when displayed
+    pub(crate) fn width(&self) -> usize {
+        self.as_str().width()
+    }
+
+    /// Append as many chars as fit.
+    pub(crate) fn push_str(&mut self, s: &str) {
+        for c in s.chars() {
+            let len = self.len as usize;
+            let char_len = c.len_utf8();
+            if len + char_len > CAPACITY {
+                break;
+            }
+            c.encode_utf8(&mut self.bytes[len..]);
+            self.len += char_len as u8;
+        }
+    }
+}
+
+impl From<char> for Grapheme {
+    fn from(c: char) -> Self {
+        Self::new(c.encode_utf8(&mut [0; 4]))
+    }
+}
+
+impl From<&str> for Grapheme {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl fmt::Debug for Grapheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl fmt::Display for Grapheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn width() {
+        assert_eq!(Grapheme::from('a').width(), 1);
+        assert_eq!(Grapheme::new("e\u{301}").width(), 1);
+        assert_eq!(Grapheme::new("🇸🇪").width(), 2);
+        assert_eq!(Grapheme::new("👩‍👩‍👧‍👦").width(), 2);
+    }
+
+    #[test]
+    fn truncate() {
+        let long = "e\u{301}".repeat(20);
+        let grapheme = Grapheme::new(&long);
+        assert!(grapheme.as_str().len() <= CAPACITY);
+        assert!(long.starts_with(grapheme.as_str()));
+    }
+}
@@ -30,6 +30,9 @@
 use std::ops::{Add, Sub};
 
 mod buffer;
+mod color;
+mod export;
+mod grapheme;
 mod screen;
 mod style;
 
@@ -37,18 +40,25 @@ mod style;
 //     - Re-exports -
 // -----------------------------------------------------------------------------
 pub use buffer::Buffer;
+pub use color::ColorDepth;
+#[cfg(feature = "widgets")]
+pub(crate) use color::{mix, to_rgb};
 pub use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 pub use crossterm::style::{Color, SetBackgroundColor, SetForegroundColor};
 pub use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, size, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen,
 };
 pub use crossterm::{cursor, ExecutableCommand, QueueableCommand};
+#[cfg(feature = "runtime")]
+pub(crate) use screen::restore_output;
 pub use screen::Screen;
-pub use style::{Attributes, Style};
+pub use style::{Attributes, Style, UnderlineStyle};
 
 pub mod events {
     //! Re-export crossterm events
     pub use crossterm::event::read;
+    #[cfg(feature = "async")]
+    pub use crossterm::event::EventStream;
     pub use crossterm::event::{
         Event as CrossEvent, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
     };
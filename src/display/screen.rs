@@ -1,19 +1,29 @@
+use std::env;
 use std::io::{Result, Write};
 
 use super::buffer::{diff, draw_changes, Buffer};
-use super::{ScreenPos, Size, Style};
+use super::{ColorDepth, ScreenPos, Size, Style};
 use crossterm::event::DisableMouseCapture;
-use crossterm::style::{Color, SetBackgroundColor, SetForegroundColor};
+use crossterm::style::{Color, Print, SetBackgroundColor, SetForegroundColor};
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen,
 };
 use crossterm::{cursor, ExecutableCommand, QueueableCommand};
 
+// Begin and end synchronized output (DEC private mode 2026).
+// The terminal holds off drawing until the end of the update, to prevent tearing.
+const BEGIN_SYNCHRONIZED_UPDATE: &str = "\x1b[?2026h";
+const END_SYNCHRONIZED_UPDATE: &str = "\x1b[?2026l";
+
 /// The `Screen` is used to draw to some `std::io::Write`able output (generally `stdout`);
 pub struct Screen {
     // This is pub(crate) for testing purposes
     pub(crate) new_buffer: Buffer,
     old_buffer: Buffer,
+    color_depth: ColorDepth,
+    inline: bool,
+    synchronized_output: bool,
+    hyperlinks: bool,
 }
 
 impl Screen {
@@ -23,15 +33,114 @@ impl Screen {
     pub fn new(mut output: impl Write, size: impl Into<Size>) -> Result<Self> {
         let size: Size = size.into();
         output.queue(cursor::Hide)?;
-        let inst = Self { old_buffer: Buffer::new(size), new_buffer: Buffer::new(size) };
+        let inst = Self {
+            old_buffer: Buffer::new(size),
+            new_buffer: Buffer::new(size),
+            color_depth: ColorDepth::TrueColor,
+            inline: false,
+            synchronized_output: false,
+            hyperlinks: true,
+        };
         Ok(inst)
     }
 
+    /// Create a new instance of a screen that renders inline, below the cursor,
+    /// rather than from the top left of the terminal.
+    ///
+    /// This reserves `size.height` lines below the cursor (scrolling the terminal if needed).
+    /// All cursor movement is relative to the top left of the reserved lines,
+    /// so the screen keeps working if the terminal scrolls.
+    pub fn new_inline(mut output: impl Write, size: impl Into<Size>) -> Result<Self> {
+        let size: Size = size.into();
+        let mut inst = Self::new(&mut output, size)?;
+        inst.inline = true;
+
+        let lines = size.height.saturating_sub(1) as u16;
+        for _ in 0..lines {
+            output.queue(Print('\n'))?;
+        }
+        if lines > 0 {
+            output.queue(cursor::MoveUp(lines))?;
+        }
+        output.queue(cursor::MoveToColumn(0))?;
+        output.flush()?;
+
+        Ok(inst)
+    }
+
+    /// Returns true if the screen renders inline.
+    pub fn is_inline(&self) -> bool {
+        self.inline
+    }
+
     /// Access to the current buffer
     pub fn buffer(&self) -> &Buffer {
         &self.new_buffer
     }
 
+    /// The colour depth used when rendering.
+    pub fn color_depth(&self) -> ColorDepth {
+        self.color_depth
+    }
+
+    /// Set the colour depth used when rendering.
+    /// Colours are converted to the closest colour of the given depth.
+    /// The default is [`ColorDepth::TrueColor`].
+    pub fn set_color_depth(&mut self, color_depth: ColorDepth) {
+        self.color_depth = color_depth;
+    }
+
+    /// Returns true if every frame is wrapped in a synchronized update.
+    pub fn synchronized_output(&self) -> bool {
+        self.synchronized_output
+    }
+
+    /// Wrap every rendered frame in a synchronized update (DEC mode 2026),
+    /// so the terminal draws the entire frame at once.
+    /// The default is `false`, see [`Screen::detect_synchronized_output`].
+    pub fn set_synchronized_output(&mut self, synchronized_output: bool) {
+        self.synchronized_output = synchronized_output;
+    }
+
+    /// Returns true if links are rendered as hyperlinks.
+    pub fn hyperlinks(&self) -> bool {
+        self.hyperlinks
+    }
+
+    /// Render links (see [`Screen::put_link`]) as clickable hyperlinks (OSC 8).
+    /// If this is disabled links are rendered as plain text.
+    /// The default is `true`.
+    pub fn set_hyperlinks(&mut self, hyperlinks: bool) {
+        self.hyperlinks = hyperlinks;
+    }
+
+    /// Detect if the terminal supports synchronized updates from the environment
+    /// (`TERM_PROGRAM` and `TERM`).
+    ///
+    /// Terminals that don't support it should ignore the sequence, however
+    /// this only returns true for terminals known to support it.
+    pub fn detect_synchronized_output() -> bool {
+        Self::synchronized_output_from_env(env::var("TERM_PROGRAM").ok().as_deref(), env::var("TERM").ok().as_deref())
+    }
+
+    fn synchronized_output_from_env(term_program: Option<&str>, term: Option<&str>) -> bool {
+        const PROGRAMS: &[&str] = &["WezTerm", "iTerm.app", "vscode", "ghostty", "contour", "rio"];
+        const TERMS: &[&str] = &["kitty", "foot", "alacritty", "wezterm", "contour", "ghostty"];
+
+        if let Some(program) = term_program {
+            if PROGRAMS.contains(&program) {
+                return true;
+            }
+        }
+
+        term.map(|term| TERMS.iter().any(|t| term.contains(t))).unwrap_or(false)
+    }
+
+    /// The last rendered frame.
+    pub fn rendered(&self) -> &Buffer {
+        &self.old_buffer
+    }
+
     /// The size of the underlying buffer
     pub fn size(&self) -> Size {
         self.new_buffer.size()
@@ -45,7 +154,7 @@ impl Screen {
         self.new_buffer = Buffer::new(new_size);
     }
 
-    /// Clear the entire screen.
+    /// Clear the entire screen (or only the reserved lines when rendering inline).
     /// If anything was written to the screen (e.g through [`put`](Self::put)) it will be cleared
     /// as well.
     ///
@@ -56,17 +165,23 @@ impl Screen {
     pub fn clear_all(&mut self, mut output: impl Write) -> Result<()> {
         self.erase();
         output.flush()?;
-        output.queue(cursor::MoveTo(0, 0))?;
+        match self.inline {
+            true => output.queue(cursor::MoveToColumn(0))?,
+            false => output.queue(cursor::MoveTo(0, 0))?,
+        };
         output.queue(SetForegroundColor(Color::Reset))?;
         output.queue(SetBackgroundColor(Color::Reset))?;
-        output.queue(Clear(ClearType::All))?;
+        match self.inline {
+            true => output.queue(Clear(ClearType::FromCursorDown))?,
+            false => output.queue(Clear(ClearType::All))?,
+        };
         output.flush()?;
         Ok(())
     }
 
     /// Erase the entire buffer by writing empty cells
     pub fn erase(&mut self) {
-        self.erase_region(ScreenPos::ZERO, self.size());
+        self.new_buffer.erase();
     }
 
     /// Erase a specific region.
@@ -89,23 +204,58 @@ impl Screen {
         self.new_buffer.put_char(c, style, pos);
     }
 
-    /// Get character and style at a given sceen position
-    pub fn get(&self, pos: ScreenPos) -> Option<(char, Style)> {
+    /// Put a grapheme cluster (e.g `"e\u{301}"` or `"🇸🇪"`) at the given screen position, with a given style.
+    /// See [`Buffer::put_grapheme`].
+    pub fn put_grapheme(&mut self, grapheme: &str, style: Style, pos: ScreenPos) {
+        self.new_buffer.put_grapheme(grapheme, style, pos);
+    }
+
+    /// Put a grapheme cluster at the given screen position, with a given style, linking to `url`.
+    /// See [`Buffer::put_link`].
+    pub fn put_link(&mut self, grapheme: &str, style: Style, url: &str, pos: ScreenPos) {
+        self.new_buffer.put_link(grapheme, style, url, pos);
+    }
+
+    /// Blend a background colour over what is already at the given screen position.
+    /// See [`Buffer::blend_background`].
+    pub fn blend_background(&mut self, color: Color, opacity: f32, pos: ScreenPos) {
+        self.new_buffer.blend_background(color, opacity, pos);
+    }
+
+    /// Get the grapheme cluster and style at a given sceen position
+    pub fn get(&self, pos: ScreenPos) -> Option<(&str, Style)> {
         self.new_buffer.get(pos)
     }
 
     /// Draw the changes to the screen
     pub fn render(&mut self, mut output: impl Write) -> Result<()> {
-        let changes = diff(&self.old_buffer, &self.new_buffer)?;
+        let mut changes = diff(&self.old_buffer, &self.new_buffer)?;
 
         if changes.is_empty() {
             return Ok(());
         }
 
-        draw_changes(&mut output, changes)?;
+        if self.color_depth != ColorDepth::TrueColor {
+            changes.iter_mut().for_each(|(_, style, _)| *style = style.map(|s| self.color_depth.apply(s)));
+        }
+
+        if self.synchronized_output {
+            output.queue(Print(BEGIN_SYNCHRONIZED_UPDATE))?;
+        }
+
+        let links = match self.hyperlinks {
+            true => self.new_buffer.links(),
+            false => &[],
+        };
+        draw_changes(&mut output, changes, self.inline, links)?;
+
+        if self.synchronized_output {
+            output.queue(Print(END_SYNCHRONIZED_UPDATE))?;
+        }
+
         output.flush()?;
 
-        self.old_buffer = self.new_buffer.clone();
+        self.old_buffer.clone_from(&self.new_buffer);
 
         Ok(())
     }
@@ -131,14 +281,22 @@ impl Screen {
 
     /// Restore the terminal by setting the cursor to show, disable raw mode, disable mouse capture
     /// and leave any alternative screens
-    pub fn restore(&mut self, mut output: impl Write) -> Result<()> {
-        disable_raw_mode()?;
+    pub fn restore(&mut self, output: impl Write) -> Result<()> {
+        restore_output(output, true)
+    }
+}
+
+// Disable raw mode, leave the alternate screen (if it was entered), disable the mouse and show the cursor.
+// Leaving the alternate screen also restores the cursor, so it's only done if it was entered.
+pub(crate) fn restore_output(mut output: impl Write, alt_screen: bool) -> Result<()> {
+    disable_raw_mode()?;
+    if alt_screen {
         output.execute(LeaveAlternateScreen)?;
-        #[cfg(not(target_os = "windows"))]
-        output.execute(DisableMouseCapture)?;
-        output.execute(cursor::Show)?;
-        Ok(())
     }
+    #[cfg(not(target_os = "windows"))]
+    output.execute(DisableMouseCapture)?;
+    output.execute(cursor::Show)?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -198,6 +356,119 @@ mod test {
         assert_eq!(Cell::empty(), actual);
     }
 
+    #[test]
+    fn render_color_depth() {
+        let mut render_output = vec![];
+        let mut screen = make_screen(Size::new(1, 1), &mut render_output);
+        screen.set_color_depth(ColorDepth::Ansi256);
+        let mut style = Style::new();
+        style.set_fg(Color::Rgb { r: 255, g: 0, b: 0 });
+        screen.put('x', style, ScreenPos::ZERO);
+        screen.render(&mut render_output).unwrap();
+
+        let output = String::from_utf8(render_output).unwrap();
+        assert!(output.contains("\x1b[38;5;196m"));
+        assert!(!output.contains("38;2;"));
+    }
+
+    #[test]
+    fn synchronized_output() {
+        let mut render_output = vec![];
+        let mut screen = make_screen(Size::new(2, 1), &mut render_output);
+        screen.set_synchronized_output(true);
+        screen.render(&mut render_output).unwrap();
+
+        let output = String::from_utf8(render_output).unwrap();
+        assert!(output.contains("\x1b[?2026h\x1b[1;1H"));
+        assert!(output.ends_with("00\x1b[?2026l"));
+
+        // Nothing changed, so nothing is written
+        let mut render_output = vec![];
+        screen.render(&mut render_output).unwrap();
+        assert!(render_output.is_empty());
+    }
+
+    #[test]
+    fn hyperlinks() {
+        let mut render_output = vec![];
+        let mut screen = Screen::new(&mut render_output, Size::new(2, 1)).unwrap();
+        screen.put_link("x", Style::reset(), "https://example.com", ScreenPos::ZERO);
+        screen.render(&mut render_output).unwrap();
+        let output = String::from_utf8(render_output).unwrap();
+        assert!(output.contains("\x1b]8;id=anathema-0;https://example.com\x1b\\"));
+        assert!(output.contains("x\x1b]8;;\x1b\\"));
+
+        let mut render_output = vec![];
+        let mut screen = Screen::new(&mut render_output, Size::new(2, 1)).unwrap();
+        screen.set_hyperlinks(false);
+        screen.put_link("x", Style::reset(), "https://example.com", ScreenPos::ZERO);
+        screen.render(&mut render_output).unwrap();
+        let output = String::from_utf8(render_output).unwrap();
+        assert!(!output.contains("\x1b]8"));
+    }
+
+    #[test]
+    fn links_only_live_for_a_frame() {
+        let mut render_output = vec![];
+        let mut screen = Screen::new(&mut render_output, Size::new(2, 1)).unwrap();
+
+        for i in 0..3 {
+            let url = format!("https://example.com/{i}");
+            screen.put_link("x", Style::reset(), &url, ScreenPos::ZERO);
+            assert_eq!(screen.new_buffer.links().len(), 1);
+
+            // The link id is the same for every frame, but the url changed
+            let mut render_output = vec![];
+            screen.render(&mut render_output).unwrap();
+            let output = String::from_utf8(render_output).unwrap();
+            assert!(output.contains(&format!("\x1b]8;id=anathema-0;{url}\x1b\\")));
+            screen.erase();
+        }
+
+        // Same url with a different id
+        screen.put_link("y", Style::reset(), "https://example.com/y", ScreenPos::ZERO);
+        screen.put_link("x", Style::reset(), "https://example.com/2", ScreenPos::ZERO);
+        let mut render_output = vec![];
+        screen.render(&mut render_output).unwrap();
+        assert!(render_output.is_empty());
+    }
+
+    #[test]
+    fn detect_synchronized_output() {
+        assert!(Screen::synchronized_output_from_env(Some("WezTerm"), None));
+        assert!(Screen::synchronized_output_from_env(None, Some("xterm-kitty")));
+        assert!(!Screen::synchronized_output_from_env(Some("Apple_Terminal"), Some("xterm-256color")));
+        assert!(!Screen::synchronized_output_from_env(None, None));
+    }
+
+    #[test]
+    fn inline() {
+        let mut output = vec![];
+        let mut screen = Screen::new_inline(&mut output, Size::new(2, 3)).unwrap();
+        assert!(String::from_utf8(output).unwrap().ends_with("\n\n\x1b[2A\x1b[1G"));
+
+        let mut output = vec![];
+        screen.clear_all(&mut output).unwrap();
+        screen.put('x', Style::reset(), ScreenPos::new(1, 2));
+        screen.render(&mut output).unwrap();
+
+        // Never move the cursor to an absolute position
+        let output = String::from_utf8(output).unwrap();
+        assert!(!output.contains('H'));
+        assert!(output.contains("\x1b[2B\x1b[2G"));
+    }
+
+    #[test]
+    fn restore_leaves_alt_screen_only_if_entered() {
+        let mut output = vec![];
+        restore_output(&mut output, false).unwrap();
+        assert!(!String::from_utf8(output).unwrap().contains("\x1b[?1049l"));
+
+        let mut output = vec![];
+        restore_output(&mut output, true).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("\x1b[?1049l"));
+    }
+
     #[test]
     #[should_panic(expected = "index out of bounds: the len is 1 but the index is 4")]
     fn put_outside_of_screen() {
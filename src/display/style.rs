@@ -1,7 +1,7 @@
 use std::io::{Result, Write};
 
 use crossterm::style::{Attribute as CrossAttrib, SetAttribute};
-use crossterm::style::{SetBackgroundColor, SetForegroundColor};
+use crossterm::style::{SetBackgroundColor, SetForegroundColor, SetUnderlineColor};
 use crossterm::QueueableCommand;
 
 use super::Color;
@@ -36,14 +36,102 @@ pub struct Style {
     pub fg: Option<Color>,
     /// Background colour.
     pub bg: Option<Color>,
+    /// Underline colour.
+    /// If this is not set the underline has the same colour as the foreground.
+    pub underline_color: Option<Color>,
     /// Attributes.
     pub attributes: Attributes,
 }
 
+// A group of attributes, the attributes in the group and how to turn them on,
+// and how to turn off the group.
+type AttributeGroup = (Attributes, &'static [(Attributes, CrossAttrib)], CrossAttrib);
+
+// Attributes that are turned off together. Turning off any of the attributes in a
+// group turns off every attribute in that group.
+const ATTRIBUTE_GROUPS: &[AttributeGroup] = &[
+    (
+        Attributes::INTENSITY,
+        &[(Attributes::BOLD, CrossAttrib::Bold), (Attributes::DIM, CrossAttrib::Dim)],
+        CrossAttrib::NormalIntensity,
+    ),
+    (Attributes::ITALIC, &[(Attributes::ITALIC, CrossAttrib::Italic)], CrossAttrib::NoItalic),
+    (
+        Attributes::UNDERLINES,
+        &[
+            (Attributes::UNDERLINED, CrossAttrib::Underlined),
+            (Attributes::DOUBLE_UNDERLINED, CrossAttrib::DoubleUnderlined),
+            (Attributes::CURLY_UNDERLINED, CrossAttrib::Undercurled),
+            (Attributes::DOTTED_UNDERLINED, CrossAttrib::Underdotted),
+            (Attributes::DASHED_UNDERLINED, CrossAttrib::Underdashed),
+        ],
+        CrossAttrib::NoUnderline,
+    ),
+    (Attributes::OVERLINED, &[(Attributes::OVERLINED, CrossAttrib::OverLined)], CrossAttrib::NotOverLined),
+    (Attributes::CROSSED_OUT, &[(Attributes::CROSSED_OUT, CrossAttrib::CrossedOut)], CrossAttrib::NotCrossedOut),
+    (Attributes::INVERSE, &[(Attributes::INVERSE, CrossAttrib::Reverse)], CrossAttrib::NoReverse),
+    (
+        Attributes::BLINKS,
+        &[(Attributes::SLOW_BLINK, CrossAttrib::SlowBlink), (Attributes::RAPID_BLINK, CrossAttrib::RapidBlink)],
+        CrossAttrib::NoBlink,
+    ),
+    (Attributes::HIDDEN, &[(Attributes::HIDDEN, CrossAttrib::Hidden)], CrossAttrib::NoHidden),
+];
+
+/// The style of the underline.
+/// Not every terminal supports every style, and will generally
+/// fall back to a single underline.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UnderlineStyle {
+    /// A single straight line
+    Single,
+    /// Two straight lines
+    Double,
+    /// A wavy line, commonly used for diagnostics
+    Curly,
+    /// A dotted line
+    Dotted,
+    /// A dashed line
+    Dashed,
+}
+
+impl UnderlineStyle {
+    fn attribute(&self) -> Attributes {
+        match self {
+            Self::Single => Attributes::UNDERLINED,
+            Self::Double => Attributes::DOUBLE_UNDERLINED,
+            Self::Curly => Attributes::CURLY_UNDERLINED,
+            Self::Dotted => Attributes::DOTTED_UNDERLINED,
+            Self::Dashed => Attributes::DASHED_UNDERLINED,
+        }
+    }
+}
+
+impl std::str::FromStr for UnderlineStyle {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "single" => Ok(Self::Single),
+            "double" => Ok(Self::Double),
+            "curly" => Ok(Self::Curly),
+            "dotted" => Ok(Self::Dotted),
+            "dashed" => Ok(Self::Dashed),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Style {
     /// Create a new instance of a `Style`:
     pub const fn new() -> Self {
-        Self { fg: None, bg: None, attributes: Attributes::empty() }
+        Self { fg: None, bg: None, underline_color: None, attributes: Attributes::empty() }
     }
 
     pub(crate) fn write(&self, w: &mut impl Write) -> Result<()> {
@@ -55,50 +143,70 @@ impl Style {
             w.queue(SetBackgroundColor(bg))?;
         }
 
-        // Dim and bold are a special case, as they are both
-        // reset through `NormalIntensity` (22).
-        // This means the reset has to happen before setting
-        // bold or dim
-        if !self.attributes.contains(Attributes::BOLD | Attributes::DIM) {
-            w.queue(SetAttribute(CrossAttrib::NormalIntensity))?;
+        if let Some(color) = self.underline_color {
+            w.queue(SetUnderlineColor(color))?;
         }
 
-        if self.attributes.contains(Attributes::BOLD) {
-            w.queue(SetAttribute(CrossAttrib::Bold))?;
-        }
+        // Turn off every group of attributes first, as e.g dim and bold are
+        // both turned off through `NormalIntensity` (22).
+        for (group, attributes, off) in ATTRIBUTE_GROUPS {
+            if !self.attributes.intersects(*group) {
+                w.queue(SetAttribute(*off))?;
+                continue;
+            }
 
-        if self.attributes.contains(Attributes::DIM) {
-            w.queue(SetAttribute(CrossAttrib::Dim))?;
-        }
+            if *group == Attributes::INTENSITY {
+                w.queue(SetAttribute(*off))?;
+            }
 
-        if self.attributes.contains(Attributes::ITALIC) {
-            w.queue(SetAttribute(CrossAttrib::Italic))?;
-        } else {
-            w.queue(SetAttribute(CrossAttrib::NoItalic))?;
+            for (attribute, on) in attributes.iter() {
+                if self.attributes.contains(*attribute) {
+                    w.queue(SetAttribute(*on))?;
+                }
+            }
         }
 
-        if self.attributes.contains(Attributes::UNDERLINED) {
-            w.queue(SetAttribute(CrossAttrib::Underlined))?;
-        } else {
-            w.queue(SetAttribute(CrossAttrib::NoUnderline))?;
+        Ok(())
+    }
+
+    // Write only what differs from the style the output is currently using.
+    // Without a previous style the entire style is written.
+    pub(crate) fn write_diff(&self, previous: Option<&Style>, w: &mut impl Write) -> Result<()> {
+        let previous = match previous {
+            Some(previous) => previous,
+            None => return self.write(w),
+        };
+
+        if let Some(fg) = self.fg.filter(|fg| previous.fg != Some(*fg)) {
+            w.queue(SetForegroundColor(fg))?;
         }
 
-        if self.attributes.contains(Attributes::OVERLINED) {
-            w.queue(SetAttribute(CrossAttrib::OverLined))?;
-        } else {
-            w.queue(SetAttribute(CrossAttrib::NotOverLined))?;
+        if let Some(bg) = self.bg.filter(|bg| previous.bg != Some(*bg)) {
+            w.queue(SetBackgroundColor(bg))?;
         }
 
-        if self.attributes.contains(Attributes::CROSSED_OUT) {
-            w.queue(SetAttribute(CrossAttrib::CrossedOut))?;
-        } else {
-            w.queue(SetAttribute(CrossAttrib::NotCrossedOut))?;
+        // Unlike the foreground and background, an underline colour is only used by the cells that set it,
+        // so a colour from the previous cells is reset
+        if self.underline_color != previous.underline_color {
+            w.queue(SetUnderlineColor(self.underline_color.unwrap_or(Color::Reset)))?;
         }
 
-        if self.attributes.contains(Attributes::INVERSE) {
-            w.queue(SetAttribute(CrossAttrib::Reverse))?;
-        } else {
-            w.queue(SetAttribute(CrossAttrib::NoReverse))?;
+        let added = self.attributes - previous.attributes;
+        let removed = previous.attributes - self.attributes;
+
+        for (group, attributes, off) in ATTRIBUTE_GROUPS {
+            // Turning off any attribute in the group turns off all of them,
+            // so whichever attributes are still set have to be set again.
+            let reset = removed.intersects(*group);
+            if reset {
+                w.queue(SetAttribute(*off))?;
+            }
+
+            for (attribute, on) in attributes.iter() {
+                if added.contains(*attribute) || (reset && self.attributes.contains(*attribute)) {
+                    w.queue(SetAttribute(*on))?;
+                }
+            }
         }
 
         Ok(())
@@ -142,11 +250,60 @@ impl Style {
     }
 
     /// Make the cell underlined as long as it's supported
+    /// Removing the underline removes any style of underline.
     pub fn set_underlined(&mut self, underlined: bool) {
-        if underlined {
-            self.attributes |= Attributes::UNDERLINED;
+        self.set_underline_style(underlined.then_some(UnderlineStyle::Single));
+    }
+
+    /// Set the style of the underline, or remove the underline with `None`.
+    /// The cell can only have one style of underline.
+    pub fn set_underline_style(&mut self, underline_style: Option<UnderlineStyle>) {
+        self.attributes &= !Attributes::UNDERLINES;
+        if let Some(underline_style) = underline_style {
+            self.attributes |= underline_style.attribute();
+        }
+    }
+
+    /// The style of the underline, if the cell is underlined
+    pub fn underline_style(&self) -> Option<UnderlineStyle> {
+        [
+            UnderlineStyle::Single,
+            UnderlineStyle::Double,
+            UnderlineStyle::Curly,
+            UnderlineStyle::Dotted,
+            UnderlineStyle::Dashed,
+        ]
+        .into_iter()
+        .find(|underline_style| self.attributes.contains(underline_style.attribute()))
+    }
+
+    /// Set the colour of the underline
+    pub fn set_underline_color(&mut self, color: Color) {
+        self.underline_color = Some(color);
+    }
+
+    /// Make the cell blink slowly as long as it's supported
+    pub fn set_blink(&mut self, blink: bool) {
+        self.attributes &= !Attributes::BLINKS;
+        if blink {
+            self.attributes |= Attributes::SLOW_BLINK;
+        }
+    }
+
+    /// Make the cell blink rapidly as long as it's supported
+    pub fn set_rapid_blink(&mut self, rapid_blink: bool) {
+        self.attributes &= !Attributes::BLINKS;
+        if rapid_blink {
+            self.attributes |= Attributes::RAPID_BLINK;
+        }
+    }
+
+    /// Hide the characters in the cell
+    pub fn set_hidden(&mut self, hidden: bool) {
+        if hidden {
+            self.attributes |= Attributes::HIDDEN;
         } else {
-            self.attributes &= !Attributes::UNDERLINED;
+            self.attributes &= !Attributes::HIDDEN;
         }
     }
 
@@ -188,6 +345,7 @@ impl Style {
     /// Merge two styles:
     /// if `self` has no foreground the foreground from the other style is copied to self.
     /// if `self` has no background the background from the other style is copied to self.
+    /// if `self` has no underline colour the underline colour from the other style is copied to self.
     pub fn merge(&mut self, other: Style) {
         if let (None, Some(fg)) = (self.fg, other.fg) {
             self.fg = Some(fg);
@@ -197,26 +355,125 @@ impl Style {
             self.bg = Some(bg);
         }
 
+        if let (None, Some(color)) = (self.underline_color, other.underline_color) {
+            self.underline_color = Some(color);
+        }
+
         self.attributes |= other.attributes;
     }
 }
 
 bitflags::bitflags! {
     /// Style attributes
-    pub struct Attributes: u8 {
+    pub struct Attributes: u16 {
         /// Make the characters bold (in supported output)
-        const BOLD =        0b0000_0001;
+        const BOLD =              0b0000_0000_0000_0001;
         /// Make the characters dim (in supported output)
-        const DIM =         0b0000_0010;
+        const DIM =               0b0000_0000_0000_0010;
         /// Make the characters italic (in supported output)
-        const ITALIC =      0b0000_0100;
+        const ITALIC =            0b0000_0000_0000_0100;
         /// Make the characters underlined (in supported output)
-        const UNDERLINED =  0b0000_1000;
+        const UNDERLINED =        0b0000_0000_0000_1000;
         /// Make the characters crossed out (in supported output)
-        const CROSSED_OUT = 0b0001_0000;
+        const CROSSED_OUT =       0b0000_0000_0001_0000;
         /// Make the characters overlined (in supported output)
-        const OVERLINED =   0b0010_0000;
+        const OVERLINED =         0b0000_0000_0010_0000;
         /// Make the characters inverse (in supported output)
-        const INVERSE =     0b0100_0000;
+        const INVERSE =           0b0000_0000_0100_0000;
+        /// Underline the characters with two lines (in supported output)
+        const DOUBLE_UNDERLINED = 0b0000_0000_1000_0000;
+        /// Underline the characters with a wavy line (in supported output)
+        const CURLY_UNDERLINED =  0b0000_0001_0000_0000;
+        /// Underline the characters with a dotted line (in supported output)
+        const DOTTED_UNDERLINED = 0b0000_0010_0000_0000;
+        /// Underline the characters with a dashed line (in supported output)
+        const DASHED_UNDERLINED = 0b0000_0100_0000_0000;
+        /// Make the characters blink (in supported output)
+        const SLOW_BLINK =        0b0000_1000_0000_0000;
+        /// Make the characters blink rapidly (in supported output)
+        const RAPID_BLINK =       0b0001_0000_0000_0000;
+        /// Hide the characters (in supported output)
+        const HIDDEN =            0b0010_0000_0000_0000;
+
+        /// Bold and dim
+        const INTENSITY = Self::BOLD.bits | Self::DIM.bits;
+        /// Every style of underline
+        const UNDERLINES = Self::UNDERLINED.bits
+            | Self::DOUBLE_UNDERLINED.bits
+            | Self::CURLY_UNDERLINED.bits
+            | Self::DOTTED_UNDERLINED.bits
+            | Self::DASHED_UNDERLINED.bits;
+        /// Slow and rapid blink
+        const BLINKS = Self::SLOW_BLINK.bits | Self::RAPID_BLINK.bits;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn written(style: Style, previous: Option<Style>) -> String {
+        let mut output = vec![];
+        style.write_diff(previous.as_ref(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn write_extended_attributes() {
+        let mut style = Style::new();
+        style.set_underline_style(Some(UnderlineStyle::Curly));
+        style.set_underline_color(Color::Red);
+        style.set_rapid_blink(true);
+        style.set_hidden(true);
+
+        let output = written(style, None);
+        assert!(output.contains("\x1b[58;5;9m"));
+        assert!(output.contains("\x1b[4:3m"));
+        assert!(output.contains("\x1b[6m"));
+        assert!(output.contains("\x1b[8m"));
+        assert!(!output.contains("\x1b[24m"));
+    }
+
+    #[test]
+    fn write_diff() {
+        let mut previous = Style::new();
+        previous.set_bold(true);
+        previous.set_dim(true);
+        previous.set_underline_style(Some(UnderlineStyle::Curly));
+
+        let mut style = previous;
+        style.set_dim(false);
+        style.set_underline_style(Some(UnderlineStyle::Dotted));
+
+        // Turning off dim turns off bold as well.
+        // The curly underline is turned off before the dotted underline is turned on.
+        assert_eq!(written(style, Some(previous)), "\x1b[22m\x1b[1m\x1b[24m\x1b[4:4m");
+        assert_eq!(written(style, Some(style)), "");
+    }
+
+    #[test]
+    fn write_diff_underline_color() {
+        let mut previous = Style::new();
+        previous.set_underline_color(Color::Red);
+
+        let mut style = previous;
+        style.set_underline_color(Color::Blue);
+        assert_eq!(written(style, Some(previous)), "\x1b[58;5;12m");
+
+        // The colour is reset rather than left to the next cells
+        assert_eq!(written(Style::new(), Some(previous)), "\x1b[59m");
+        assert_eq!(written(Style::new(), Some(Style::new())), "");
+    }
+
+    #[test]
+    fn underline_style() {
+        let mut style = Style::new();
+        style.set_underlined(true);
+        assert_eq!(style.underline_style(), Some(UnderlineStyle::Single));
+        style.set_underline_style(Some(UnderlineStyle::Double));
+        assert_eq!(style.underline_style(), Some(UnderlineStyle::Double));
+        assert!(!style.attributes.contains(Attributes::UNDERLINED));
+        style.set_underline_style(None);
+        assert_eq!(style.underline_style(), None);
     }
 }
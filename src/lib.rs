@@ -1,5 +1,9 @@
 #![warn(rust_2018_idioms)]
 
+// The derive macros refer to `::anathema`, which has to resolve in the tests as well
+#[cfg(all(test, feature = "derive"))]
+extern crate self as anathema;
+
 pub(crate) mod antstring;
 pub mod display;
 #[cfg(feature = "runtime")]
@@ -4,14 +4,18 @@ use std::time::{Duration, Instant};
 use crate::display::{Screen, Size};
 use crate::templates::diff;
 use crate::templates::{
-    build_widget_tree, to_nodes, DataCtx, IncludeCache, Node, NodeCtx, SubContext, WidgetLookup, WidgetNode,
+    build_widget_tree, to_nodes, update_nodes, validate, DataCtx, IncludeCache, Node, NodeCtx, Stylesheet, SubContext,
+    Theme, WidgetLookup, WidgetNode,
 };
 use crate::widgets::{Constraints, PaintCtx, Pos, Value, WidgetContainer};
 
+use super::crash::CrashReport;
 use super::error::{Error, Result};
+use super::record::EventLog;
+use super::timers::{TimerHandle, Timers};
 use super::Output;
 
-pub use super::events::{Event, Events, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+pub use super::events::{Event, Events};
 
 #[cfg(feature = "flume")]
 pub(crate) type Receiver<T> = flume::Receiver<Event<T>>;
@@ -86,6 +90,11 @@ pub struct AppState<T: UserModel, O: Output> {
     wait_for: WaitFor,
     widget_lookup: WidgetLookup,
     include_cache: IncludeCache,
+    stylesheet: Stylesheet,
+    // Render the next frame, even if no widget needs to be painted
+    dirty: bool,
+    // Lay out every widget on the next frame
+    force_layout: bool,
 
     tick: Instant,
     metrics: Metrics,
@@ -100,19 +109,38 @@ impl<T: UserModel, O: Output> AppState<T, O> {
         mut output: O,
         wait_for: WaitFor,
     ) -> Result<Self> {
+        // Catch mistakes in the template before anything is drawn
+        validate(&nodes, &widget_lookup).map_err(Error::Validation)?;
+
         let size = output.size();
 
         // -----------------------------------------------------------------------------
         //     - Setup the "screen" -
         // -----------------------------------------------------------------------------
-        let mut screen = Screen::new(&mut output, size)?;
+        let mut screen = match output.is_inline() {
+            true => Screen::new_inline(&mut output, size)?,
+            false => Screen::new(&mut output, size)?,
+        };
+        screen.set_color_depth(output.color_depth());
+        screen.set_synchronized_output(output.synchronized_output());
+        screen.set_hyperlinks(output.hyperlinks());
         screen.clear_all(&mut output)?;
 
         // -----------------------------------------------------------------------------
         //     - Data context -
         //     ... and add an empty `IncludeCache`
         // -----------------------------------------------------------------------------
+        let metrics = Metrics {
+            render_time: Duration::new(0, 0),
+            update_time: Duration::new(0, 0),
+            layout_time: Duration::new(0, 0),
+            paint_time: Duration::new(0, 0),
+            frame_time: Duration::new(0, 0),
+        };
+
         let ctx = user_model.data();
+        #[cfg(feature = "metrics")]
+        ctx.set("context", extra_context(size, &metrics));
         let sub_context = SubContext::new(ctx);
         let mut include_cache = IncludeCache::default();
         let mut node_ctx = NodeCtx::new(&mut include_cache);
@@ -143,46 +171,53 @@ impl<T: UserModel, O: Output> AppState<T, O> {
             output,
             wait_for,
             include_cache,
+            stylesheet: Stylesheet::default(),
+            dirty: true,
+            force_layout: false,
 
             tick: Instant::now(),
-            metrics: Metrics {
-                render_time: Duration::new(0, 0),
-                update_time: Duration::new(0, 0),
-                layout_time: Duration::new(0, 0),
-                paint_time: Duration::new(0, 0),
-                frame_time: Duration::new(0, 0),
-            },
+            metrics,
         };
 
         Ok(inst)
     }
 
-    fn handle_events(&mut self, blocking: bool) -> Result<Run> {
-        while let Some(event) = self.events_src.next_event(blocking) {
-            if let Event::Quit = event {
-                return Ok(Run::Quit);
-            }
+    /// Replace the current theme.
+    /// Every widget using the theme will be updated on the next call to [`Self::update`].
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.user_model.data().set_theme(theme);
+    }
 
-            if let Event::ReplaceWidgets(new_nodes) = event {
-                self.nodes = new_nodes;
-                self.rebuild_widgets(true)?;
-                return Ok(Run::Continue);
-            }
+    /// Replace the styles available to the template, in addition to the styles declared in the template.
+    /// The widgets are updated straight away.
+    pub fn set_stylesheet(&mut self, stylesheet: Stylesheet) -> Result<()> {
+        self.stylesheet = stylesheet;
+        self.rebuild_widgets(true)
+    }
 
-            if let Event::Resize(new_size) = event {
-                self.root.resize(new_size);
+    /// Send the message to the user model once, after the given duration.
+    pub fn schedule_once(&self, after: Duration, message: T::Message) -> TimerHandle {
+        self.events_src.timers().schedule_once(after, message)
+    }
 
-                // 1. `erase` the buffers
-                // 2.  render
-                // 3.  create new buffers with correct size
-                // 4.  layout
-                // 5.  render
+    /// Send the message to the user model every `interval`.
+    pub fn schedule_every(&self, interval: Duration, message: T::Message) -> TimerHandle
+    where
+        T::Message: Clone,
+    {
+        self.events_src.timers().schedule_every(interval, message)
+    }
 
-                self.screen.resize(new_size);
-                self.screen.clear_all(&mut self.output)?;
-            }
+    /// The [`Timers`], e.g to give the user model a way to schedule messages.
+    pub fn timers(&self) -> Timers<T::Message> {
+        self.events_src.timers()
+    }
 
-            self.events.push_back(event);
+    pub(crate) fn handle_events(&mut self, blocking: bool) -> Result<Run> {
+        while let Some(event) = self.events_src.next_event(blocking) {
+            if let Some(run) = self.handle_event(event)? {
+                return Ok(run);
+            }
 
             // If the event loop is entirely driven by events
             // then this can't loop
@@ -194,9 +229,97 @@ impl<T: UserModel, O: Output> AppState<T, O> {
         Ok(Run::Continue)
     }
 
+    // Returns a `Run` if the event should stop the event handling
+    fn handle_event(&mut self, event: Event<T::Message>) -> Result<Option<Run>> {
+        if let Event::Quit = event {
+            return Ok(Some(Run::Quit));
+        }
+
+        if let Event::ReplaceWidgets(new_nodes) = event {
+            self.nodes = new_nodes;
+            self.rebuild_widgets(true)?;
+            return Ok(Some(Run::Continue));
+        }
+
+        if let Event::Resize(size) = event {
+            // The output might not cover the entire terminal (e.g when rendering inline)
+            self.output.resize(size);
+            let new_size = self.output.size();
+            self.root.resize(new_size);
+
+            // 1. `erase` the buffers
+            // 2.  render
+            // 3.  create new buffers with correct size
+            // 4.  layout
+            // 5.  render
+
+            self.screen.resize(new_size);
+            self.screen.clear_all(&mut self.output)?;
+
+            #[cfg(feature = "metrics")]
+            self.user_model.data().insert("context", extra_context(new_size, &self.metrics));
+        }
+
+        self.events.push_back(event);
+        Ok(None)
+    }
+
+    /// Handle an event from a source other than the [`Events`] (e.g an async stream).
+    /// The event is passed to the user model on the next call to [`Self::update`].
+    pub fn push_event(&mut self, event: Event<T::Message>) -> Result<Run> {
+        Ok(self.handle_event(event)?.unwrap_or(Run::Continue))
+    }
+
+    /// Replay recorded events, updating and rendering after every event.
+    /// The time between events is ignored.
+    pub fn replay(&mut self, log: &EventLog) -> Result<()> {
+        self.update()?;
+        self.render()?;
+
+        for (_, event) in log.events() {
+            if let Some(Run::Quit) = self.handle_event((*event).into())? {
+                break;
+            }
+            self.update()?;
+            self.render()?;
+        }
+
+        Ok(())
+    }
+
+    /// The screen. Use [`Screen::rendered`] to access the last rendered frame.
+    pub fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    /// A crash report with the last rendered frame and the widget tree.
+    pub fn crash_report(&mut self, message: impl Into<String>) -> CrashReport {
+        CrashReport {
+            message: message.into(),
+            last_frame: self.screen.rendered().to_plain_text(),
+            widget_tree: self.root.stringify(),
+        }
+    }
+
+    /// The output.
+    pub fn output(&self) -> &O {
+        &self.output
+    }
+
+    /// The events source.
+    pub fn events(&self) -> &Events<T::Message> {
+        &self.events_src
+    }
+
     pub fn update(&mut self) -> Result<()> {
         let now = Instant::now();
 
+        // The user model has access to the widgets, so any event could change them
+        if !self.events.is_empty() {
+            self.dirty = true;
+            self.force_layout = true;
+        }
+
         while let Some(event) = self.events.pop_front() {
             self.user_model.event(event, &mut self.root);
         }
@@ -209,28 +332,46 @@ impl<T: UserModel, O: Output> AppState<T, O> {
 
     fn rebuild_widgets(&mut self, force_rebuild: bool) -> Result<()> {
         let ctx = self.user_model.data();
+        if !ctx.has_changes() && !force_rebuild {
+            return Ok(());
+        }
+
+        // The metrics alone should not cause another frame,
+        // so they are only updated along with everything else
         #[cfg(feature = "metrics")]
         ctx.insert("context", extra_context(self.screen.size(), &self.metrics));
 
-        if !ctx.diff().is_empty() || force_rebuild {
-            let sub_context = SubContext::new(ctx);
-            let mut node_ctx = NodeCtx::new(&mut self.include_cache);
-            let new_nodes = to_nodes(&self.nodes, &sub_context, &mut node_ctx)?;
+        let changed = ctx.take_changes();
+        let sub_context = SubContext::new(ctx);
+        let mut node_ctx = NodeCtx::new(&mut self.include_cache).with_stylesheet(self.stylesheet.clone());
+        let new_nodes = match force_rebuild || self.old_nodes.is_empty() {
+            true => to_nodes(&self.nodes, &sub_context, &mut node_ctx)?,
+            false => update_nodes(&self.nodes, &sub_context, &mut node_ctx, &self.old_nodes, &changed)?,
+        };
 
-            if !self.old_nodes.is_empty() {
-                let changes = diff::diff(&new_nodes[0], self.old_nodes.remove(0));
-                if !changes.is_empty() {
-                    changes.apply(&mut self.root, &self.widget_lookup, &new_nodes);
-                }
+        if !self.old_nodes.is_empty() {
+            let changes = diff::diff(&new_nodes[0], self.old_nodes.remove(0));
+            if !changes.is_empty() {
+                changes.apply(&mut self.root, &self.widget_lookup, &new_nodes);
             }
-
-            self.old_nodes = new_nodes;
         }
 
+        self.old_nodes = new_nodes;
+        self.dirty = true;
+
         Ok(())
     }
 
+    /// Lay out, paint and render the widgets.
+    ///
+    /// Nothing is rendered unless the data context changed, an event was handled,
+    /// a widget needs to be painted or an animation is running.
     pub fn render(&mut self) -> Result<()> {
+        if !self.dirty && !self.root.is_animating() && !self.root.needs_paint() {
+            self.tick = Instant::now();
+            return Ok(());
+        }
+
         let size = self.screen.size();
         let constraints = Constraints::new(size.width, size.height);
 
@@ -238,7 +379,7 @@ impl<T: UserModel, O: Output> AppState<T, O> {
         let now = Instant::now();
         let dt = self.tick.elapsed();
         self.root.animate(dt);
-        self.root.layout(constraints, false);
+        self.root.layout(constraints, self.force_layout);
         self.root.position(Pos::ZERO);
         self.metrics.layout_time = now.elapsed();
         self.tick = Instant::now();
@@ -255,6 +396,9 @@ impl<T: UserModel, O: Output> AppState<T, O> {
         self.screen.erase();
         self.metrics.render_time = now.elapsed();
 
+        self.dirty = false;
+        self.force_layout = false;
+
         Ok(())
     }
 
@@ -319,3 +463,116 @@ pub trait UserModel {
 
     fn data(&mut self) -> &mut DataCtx;
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::display::{Color, ScreenPos};
+    use crate::runtime::record::Headless;
+    use crate::templates::parse;
+
+    struct Model(DataCtx);
+
+    impl UserModel for Model {
+        type Message = String;
+
+        fn event(&mut self, event: Event<String>, _: &mut WidgetContainer) {
+            if let Some(s) = event.user() {
+                self.0.insert("text", s);
+            }
+        }
+
+        fn data(&mut self) -> &mut DataCtx {
+            &mut self.0
+        }
+    }
+
+    #[test]
+    fn undo_skips_runtime_values() {
+        let mut data = DataCtx::empty();
+        data.enable_history(10);
+        data.insert("text", "a");
+        data.insert("text", "b");
+        let nodes = parse("text: \"{{ text }}\"").unwrap();
+        let output = Headless::new((5u16, 1));
+        let mut app =
+            AppState::new(Model(data), Events::headless(), nodes, WidgetLookup::default(), output, WaitFor::Input)
+                .unwrap();
+
+        app.user_model.0.set_theme(&Theme::new());
+        app.update().unwrap();
+        app.push_event(Event::Resize(Size::new(6, 1))).unwrap();
+        app.update().unwrap();
+
+        assert!(app.user_model.0.undo());
+        assert_eq!(app.user_model.0.get_string("text").unwrap(), "a");
+    }
+
+    #[test]
+    fn render_only_on_change() {
+        let mut data = DataCtx::empty();
+        data.insert("text", "a");
+        let nodes = parse("border:\n    text: \"{{ text }}\"").unwrap();
+        let output = Headless::new((5u16, 3));
+        let mut app =
+            AppState::new(Model(data), Events::headless(), nodes, WidgetLookup::default(), output, WaitFor::Input)
+                .unwrap();
+
+        app.update().unwrap();
+        app.render().unwrap();
+        let written = app.output().written.len();
+        assert_eq!(app.screen().rendered().to_plain_text(), "┌─┐\n│a│\n└─┘");
+
+        // Nothing changed
+        app.update().unwrap();
+        app.render().unwrap();
+        assert_eq!(app.output().written.len(), written);
+
+        app.push_event(Event::User("b".into())).unwrap();
+        app.update().unwrap();
+        app.render().unwrap();
+        assert!(app.output().written.len() > written);
+        assert_eq!(app.screen().rendered().to_plain_text(), "┌─┐\n│b│\n└─┘");
+    }
+
+    #[test]
+    fn bound_colours_on_text() {
+        let mut theme = Theme::new();
+        theme.insert("primary", Color::Red);
+        let mut data = DataCtx::empty();
+        data.set_theme(&theme);
+        data.insert("c", Color::Blue);
+        data.insert("u", Color::Green);
+
+        let src = "vstack:\n    text [foreground: $primary]: \"a\"\n    text [background: {{ c }}, underline-color: {{ u }}]: \"b\"";
+        let nodes = parse(src).unwrap();
+        let output = Headless::new((1u16, 2));
+        let mut app =
+            AppState::new(Model(data), Events::headless(), nodes, WidgetLookup::default(), output, WaitFor::Input)
+                .unwrap();
+        app.update().unwrap();
+        app.render().unwrap();
+
+        let (_, style) = app.screen().rendered().get(ScreenPos::new(0, 0)).unwrap();
+        assert_eq!(style.fg, Some(Color::Red));
+
+        let (_, style) = app.screen().rendered().get(ScreenPos::new(0, 1)).unwrap();
+        assert_eq!(style.bg, Some(Color::Blue));
+        assert_eq!(style.underline_color, Some(Color::Green));
+    }
+
+    #[test]
+    fn validate_on_start() {
+        let nodes = parse("vstack:\n    fancy:").unwrap();
+        let output = Headless::new((5u16, 3));
+        let app = AppState::new(
+            Model(DataCtx::empty()),
+            Events::headless(),
+            nodes,
+            WidgetLookup::default(),
+            output,
+            WaitFor::Input,
+        );
+        assert!(matches!(app, Err(Error::Validation(errors)) if errors.len() == 1));
+    }
+}
@@ -0,0 +1,367 @@
+//! An async runtime, for applications built on tokio.
+//!
+//! Unlike the [`super::Runtime`] no thread is spawned to read input:
+//! terminal events are consumed as a [`Stream`], and messages can be sent from any task
+//! with a [`Spawner`], which can also spawn futures whose output is sent to the user model
+//! as [`Event::User`].
+//!
+//! Requires the `async` feature.
+use std::future::Future;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, sleep_until, MissedTickBehavior};
+
+use super::{
+    crash, error, init_theme, AppState, Event, Events, Output, OutputConfig, Run, Stdout, Timers, UserModel, WaitFor,
+};
+use crate::display::events::{CrossEvent, EventStream};
+use crate::templates::{parse, DataCtx, Stylesheet, Theme, WidgetLookup};
+use crate::widgets::WidgetContainer;
+
+// -----------------------------------------------------------------------------
+//     - Spawner -
+// -----------------------------------------------------------------------------
+/// Send messages and spawn futures whose output is sent to the user model.
+///
+/// ```ignore
+/// let spawner = runtime.spawner();
+/// spawner.spawn(async move { client.fetch().await });
+/// ```
+pub struct Spawner<T> {
+    tx: UnboundedSender<Event<T>>,
+}
+
+impl<T> Clone for Spawner<T> {
+    fn clone(&self) -> Self {
+        Self { tx: self.tx.clone() }
+    }
+}
+
+impl<T: Send + 'static> Spawner<T> {
+    /// Send a message to the user model as an [`Event::User`].
+    /// Returns `false` if the runtime has stopped.
+    pub fn send(&self, message: T) -> bool {
+        self.send_event(Event::User(message))
+    }
+
+    /// Send an event, e.g [`Event::Quit`].
+    /// Returns `false` if the runtime has stopped.
+    pub fn send_event(&self, event: Event<T>) -> bool {
+        self.tx.send(event).is_ok()
+    }
+
+    /// Spawn a future on the tokio runtime.
+    /// The output of the future is sent to the user model as an [`Event::User`].
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<()>
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        let tx = self.clone();
+        tokio::spawn(async move {
+            tx.send(future.await);
+        })
+    }
+
+    /// Forward every message from a stream to the user model.
+    /// The task ends with the stream, or once the runtime has stopped.
+    pub fn forward<S>(&self, stream: S) -> JoinHandle<()>
+    where
+        S: Stream<Item = T> + Send + 'static,
+    {
+        let tx = self.clone();
+        tokio::spawn(async move {
+            let mut stream = Box::pin(stream);
+            while let Some(message) = stream.next().await {
+                if !tx.send(message) {
+                    break;
+                }
+            }
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+//     - Runtime -
+// -----------------------------------------------------------------------------
+/// Async counterpart to the [`super::Runtime`].
+///
+/// Input is read from the terminal as a stream and every `frame_time` the
+/// [`AppState`] is updated and rendered.
+pub struct AsyncRuntime<T> {
+    /// Configuration for the output.
+    pub output_cfg: OutputConfig,
+    /// Widget lookup.
+    pub lookup: WidgetLookup,
+    /// The amount of time between render / update calls.
+    pub frame_time: Duration,
+    /// The initial theme, replacing any theme set on the data context.
+    /// Use [`DataCtx::set_theme`] to change the theme while running.
+    pub theme: Option<Theme>,
+    /// Styles available to the template, in addition to the styles declared in the template.
+    pub stylesheet: Stylesheet,
+    /// Save a [`crash::CrashReport`] to this path if the application panics.
+    pub crash_report: Option<PathBuf>,
+    events: Events<T>,
+    tx: UnboundedSender<Event<T>>,
+    rx: UnboundedReceiver<Event<T>>,
+}
+
+impl<T: Send + Sync + 'static> Default for AsyncRuntime<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Send + Sync + 'static> AsyncRuntime<T> {
+    /// Create a new instance of the async runtime.
+    pub fn new() -> Self {
+        let (tx, rx) = unbounded_channel();
+        Self {
+            output_cfg: OutputConfig::default(),
+            lookup: WidgetLookup::default(),
+            frame_time: Duration::from_millis(20),
+            theme: None,
+            stylesheet: Stylesheet::default(),
+            crash_report: None,
+            events: Events::headless(),
+            tx,
+            rx,
+        }
+    }
+
+    /// Get a [`Spawner`], used to pass messages and spawn futures.
+    pub fn spawner(&self) -> Spawner<T> {
+        Spawner { tx: self.tx.clone() }
+    }
+
+    /// Get the [`Timers`], used to schedule messages.
+    pub fn timers(&self) -> Timers<T> {
+        self.events.timers()
+    }
+
+    /// Start the runtime with a custom user model.
+    /// This has to be called from within a tokio runtime.
+    pub async fn with_usermodel(
+        self,
+        template: impl AsRef<str>,
+        mut user_model: impl UserModel<Message = T>,
+    ) -> error::Result<()> {
+        init_theme(self.theme.as_ref(), user_model.data());
+
+        let output = Stdout::new(self.output_cfg)?;
+        // Restore the terminal if anything panics from here on
+        let _guard = crash::TerminalGuard::new(output.alt_screen);
+
+        let nodes = parse(template.as_ref())?;
+        let mut app = AppState::new(user_model, self.events, nodes, self.lookup, output, WaitFor::Input)?;
+        if !self.stylesheet.is_empty() {
+            app.set_stylesheet(self.stylesheet)?;
+        }
+
+        run(app, EventStream::new(), self.rx, self.frame_time, self.crash_report).await?;
+        Ok(())
+    }
+
+    /// Start the runtime with a closure that receives every event,
+    /// see [`super::Runtime::start`].
+    pub async fn start<F>(self, template: impl AsRef<str>, initial: DataCtx, f: F) -> error::Result<()>
+    where
+        F: FnMut(Event<T>, &mut WidgetContainer, &mut DataCtx, &mut Spawner<T>),
+    {
+        let spawner = self.spawner();
+        let state = DefaultState { spawner, data: initial, f };
+        self.with_usermodel(template, state).await
+    }
+}
+
+// Drive the app state: handle input and messages as they arrive
+// and update and render on every tick.
+// Returns the app state once the runtime is told to quit.
+//
+// `select!` expands to `std::future::poll_fn`, which is newer than the crate's MSRV,
+// though tokio itself already requires a newer Rust.
+#[allow(clippy::incompatible_msrv)]
+async fn run<T, O, S>(
+    mut app: AppState<T, O>,
+    input: S,
+    mut rx: UnboundedReceiver<Event<T::Message>>,
+    frame_time: Duration,
+    crash_report: Option<PathBuf>,
+) -> error::Result<AppState<T, O>>
+where
+    T: UserModel,
+    O: Output,
+    S: Stream<Item = io::Result<CrossEvent>>,
+{
+    let mut input = Box::pin(input);
+    let mut ticks = interval(frame_time);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    loop {
+        let timers = app.timers();
+        let next_due = timers.next_due();
+
+        // Biased, so the ticks can't be starved by a busy stream
+        let wake = tokio::select! {
+            biased;
+            _ = ticks.tick() => Wake::Tick,
+            _ = sleep_until(next_due.unwrap_or_else(Instant::now).into()), if next_due.is_some() => Wake::Timers,
+            Some(event) = input.next() => Wake::Event(event?.into()),
+            Some(event) = rx.recv() => Wake::Event(event),
+        };
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| match wake {
+            Wake::Event(event) => app.push_event(event),
+            Wake::Timers => {
+                while let Some(message) = timers.pop_due(Instant::now()) {
+                    if let Run::Quit = app.push_event(Event::User(message))? {
+                        return Ok(Run::Quit);
+                    }
+                }
+                Ok(Run::Continue)
+            }
+            // Fire any timers that are due before updating
+            Wake::Tick => match app.handle_events(false)? {
+                Run::Quit => Ok(Run::Quit),
+                Run::Continue => app.update().and_then(|_| app.render()).map(|_| Run::Continue),
+            },
+        }));
+
+        match result {
+            Ok(Ok(Run::Continue)) => continue,
+            Ok(Ok(Run::Quit)) => break,
+            Ok(Err(e)) => return Err(e),
+            Err(payload) => crash::report(app, payload, crash_report.as_deref()),
+        }
+    }
+
+    Ok(app)
+}
+
+// What woke the runtime up
+enum Wake<T> {
+    Tick,
+    Timers,
+    Event(Event<T>),
+}
+
+// -----------------------------------------------------------------------------
+//     - Dummy user model -
+// -----------------------------------------------------------------------------
+struct DefaultState<T, F>
+where
+    T: Send + Sync + 'static,
+    F: FnMut(Event<T>, &mut WidgetContainer, &mut DataCtx, &mut Spawner<T>),
+{
+    spawner: Spawner<T>,
+    data: DataCtx,
+    f: F,
+}
+
+impl<T, F> UserModel for DefaultState<T, F>
+where
+    T: Send + Sync + 'static,
+    F: FnMut(Event<T>, &mut WidgetContainer, &mut DataCtx, &mut Spawner<T>),
+{
+    type Message = T;
+
+    fn event(&mut self, event: Event<Self::Message>, root: &mut WidgetContainer) {
+        (self.f)(event, root, &mut self.data, &mut self.spawner);
+    }
+
+    fn data(&mut self) -> &mut DataCtx {
+        &mut self.data
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runtime::record::Headless;
+    use crate::runtime::{KeyCode, KeyEvent, KeyModifiers};
+
+    struct Model(DataCtx, Spawner<String>);
+
+    impl UserModel for Model {
+        type Message = String;
+
+        fn event(&mut self, event: Event<String>, _: &mut WidgetContainer) {
+            let text = self.0.get_string_mut("text").unwrap();
+            match event {
+                Event::Key(KeyEvent { code: KeyCode::Char(c), .. }) => text.push(c),
+                Event::User(s) if s == "quit" => drop(self.1.send_event(Event::Quit)),
+                Event::User(s) => text.push_str(&s),
+                _ => {}
+            }
+        }
+
+        fn data(&mut self) -> &mut DataCtx {
+            &mut self.0
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(future)
+    }
+
+    #[test]
+    fn input_and_messages() {
+        let runtime = AsyncRuntime::new();
+        let spawner = runtime.spawner();
+
+        let mut data = DataCtx::empty();
+        data.insert("text", String::new());
+        let nodes = parse("text: \"{{ text }}\"").unwrap();
+        let output = Headless::new((10u16, 1));
+        let model = Model(data, spawner.clone());
+        let app =
+            AppState::new(model, Events::headless(), nodes, WidgetLookup::default(), output, WaitFor::Input).unwrap();
+
+        let key = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE);
+        let input = futures_util::stream::iter([Ok(CrossEvent::Key(key))]);
+
+        let mut app = block_on(async move {
+            spawner.spawn(async { "b".to_string() }).await.unwrap();
+            let messages = ["c", "d", "quit"].map(String::from);
+            spawner.forward(futures_util::stream::iter(messages)).await.unwrap();
+            run(app, input, runtime.rx, Duration::from_millis(1), None).await.unwrap()
+        });
+
+        assert_eq!(app.user_model.0.get_string_mut("text").unwrap(), "abcd");
+    }
+
+    #[test]
+    fn timers_between_ticks() {
+        let runtime = AsyncRuntime::new();
+        let spawner = runtime.spawner();
+        runtime.timers().schedule_once(Duration::from_millis(5), "a".to_string());
+
+        let mut data = DataCtx::empty();
+        data.insert("text", String::new());
+        let nodes = parse("text: \"{{ text }}\"").unwrap();
+        let output = Headless::new((10u16, 1));
+        let model = Model(data, spawner.clone());
+        let app = AppState::new(model, runtime.events, nodes, WidgetLookup::default(), output, WaitFor::Input).unwrap();
+
+        // Only the first tick fires before the runtime quits,
+        // so the timer has to fire on its own
+        let input = futures_util::stream::pending();
+        let mut app = block_on(async move {
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                assert!(spawner.send_event(Event::Quit));
+            });
+            run(app, input, runtime.rx, Duration::from_secs(3600), None).await.unwrap()
+        });
+
+        app.update().unwrap();
+        assert_eq!(app.user_model.0.get_string_mut("text").unwrap(), "a");
+    }
+}
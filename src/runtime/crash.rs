@@ -0,0 +1,199 @@
+//! Restore the terminal if the application panics.
+//!
+//! The [`super::Runtime`] installs a panic hook that restores the terminal (leaves raw mode
+//! and the alternate screen and shows the cursor) before the panic message is printed,
+//! as `Drop` can't be relied on once a panic crosses the runtime.
+//! Only a panic on the thread running the runtime restores the terminal:
+//! a panic on another thread (e.g a spawned task) doesn't stop the runtime.
+//!
+//! Once the panic reaches the runtime the last rendered frame is printed to stderr,
+//! and a [`CrashReport`] is saved if [`super::Runtime::crash_report`] is set.
+use std::any::Any;
+use std::cell::Cell;
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Once;
+
+use super::{AppState, Output, UserModel};
+use crate::display::restore_output;
+
+thread_local! {
+    // Set on the thread running the runtime while it owns the terminal,
+    // to whether the alternate screen was entered
+    static RAW_TERMINAL: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+// Install the panic hook (once per process).
+// The hook only restores the terminal while a `TerminalGuard` is alive on the panicking thread,
+// and always calls the previous hook, which prints the panic message.
+fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if let Ok(Some(alt_screen)) = RAW_TERMINAL.try_with(Cell::take) {
+                let _ = restore_output(io::stdout(), alt_screen);
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Restores the terminal if a panic happens on the current thread while the guard is alive.
+pub(crate) struct TerminalGuard;
+
+impl TerminalGuard {
+    pub(crate) fn new(alt_screen: bool) -> Self {
+        install_hook();
+        RAW_TERMINAL.with(|raw| raw.set(Some(alt_screen)));
+        Self
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = RAW_TERMINAL.try_with(|raw| raw.set(None));
+    }
+}
+
+/// The message of a panic payload.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(msg) => msg,
+        None => payload.downcast_ref::<String>().map(String::as_str).unwrap_or("Box<dyn Any>"),
+    }
+}
+
+/// Print the last frame to stderr and save the crash report (if there is a path),
+/// then continue unwinding.
+///
+/// The panic hook has already restored the terminal and printed the message.
+pub(crate) fn report<T: UserModel, O: Output>(
+    mut app: AppState<T, O>,
+    payload: Box<dyn Any + Send>,
+    path: Option<&Path>,
+) -> ! {
+    let report = app.crash_report(panic_message(payload.as_ref()));
+    // Drop the output first, so nothing is written after the report
+    drop(app);
+
+    eprintln!("\nlast frame:\n{}", report.last_frame);
+    if let Some(path) = path {
+        match report.save(path) {
+            Ok(()) => eprintln!("crash report saved to {}", path.display()),
+            Err(e) => eprintln!("failed to save crash report to {}: {e}", path.display()),
+        }
+    }
+
+    std::panic::resume_unwind(payload)
+}
+
+/// The state of the application when it panicked.
+///
+/// Created with [`super::AppState::crash_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashReport {
+    /// The panic message.
+    pub message: String,
+    /// The last rendered frame as plain text.
+    pub last_frame: String,
+    /// The widget tree, see [`crate::widgets::WidgetContainer::stringify`].
+    pub widget_tree: String,
+}
+
+impl CrashReport {
+    /// Save the report to a file.
+    /// If the file already exists it's replaced.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        write!(file, "{self}")?;
+        file.flush()
+    }
+}
+
+impl Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "panicked: {}", self.message)?;
+        writeln!(f)?;
+        writeln!(f, "-- last frame --")?;
+        writeln!(f, "{}", self.last_frame)?;
+        writeln!(f)?;
+        writeln!(f, "-- widgets --")?;
+        write!(f, "{}", self.widget_tree)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn message() {
+        let payload = std::panic::catch_unwind(|| panic!("static")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "static");
+
+        let payload = std::panic::catch_unwind(|| panic!("formatted {}", 1)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "formatted 1");
+
+        let payload = std::panic::catch_unwind(|| std::panic::panic_any(1)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "Box<dyn Any>");
+    }
+
+    #[test]
+    fn guard_only_covers_its_thread() {
+        let guard = TerminalGuard::new(true);
+        assert_eq!(RAW_TERMINAL.with(Cell::get), Some(true));
+        assert_eq!(std::thread::spawn(|| RAW_TERMINAL.with(Cell::get)).join().unwrap(), None);
+
+        drop(guard);
+        assert_eq!(RAW_TERMINAL.with(Cell::get), None);
+    }
+
+    #[test]
+    fn app_crash_report() {
+        use crate::runtime::record::Headless;
+        use crate::runtime::{AppState, Event, Events, UserModel, WaitFor};
+        use crate::templates::{parse, DataCtx, WidgetLookup};
+        use crate::widgets::WidgetContainer;
+
+        struct Model(DataCtx);
+
+        impl UserModel for Model {
+            type Message = ();
+
+            fn event(&mut self, _: Event<()>, _: &mut WidgetContainer) {}
+
+            fn data(&mut self) -> &mut DataCtx {
+                &mut self.0
+            }
+        }
+
+        let nodes = parse("text: \"> hi\"").unwrap();
+        let output = Headless::new((10u16, 1));
+        let mut app = AppState::new(
+            Model(DataCtx::empty()),
+            Events::headless(),
+            nodes,
+            WidgetLookup::default(),
+            output,
+            WaitFor::Input,
+        )
+        .unwrap();
+        app.render().unwrap();
+
+        let report = app.crash_report("oh no");
+        assert_eq!(report.message, "oh no");
+        assert_eq!(report.last_frame, "> hi");
+        assert!(report.widget_tree.starts_with("Text "));
+    }
+
+    #[test]
+    fn display_report() {
+        let report = CrashReport { message: "oh no".into(), last_frame: "> hi".into(), widget_tree: "Text 0\n".into() };
+
+        assert_eq!(report.to_string(), "panicked: oh no\n\n-- last frame --\n> hi\n\n-- widgets --\nText 0\n");
+    }
+}
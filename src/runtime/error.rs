@@ -6,10 +6,12 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     /// Template error
     Template(crate::templates::error::Error),
+    /// The template failed validation, see [`crate::templates::validate`].
+    Validation(Vec<crate::templates::error::Error>),
     /// IO Error
     Io(std::io::Error),
     /// Serde error
-    #[cfg(feature = "json")]
+    #[cfg(feature = "serde-json")]
     Serde(serde_json::Error),
     /// No root widget container. This happens if the template was empty.
     MissingRoot,
@@ -21,8 +23,17 @@ impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Template(e) => write!(f, "{e}"),
+            Self::Validation(errors) => {
+                for (i, e) in errors.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{e}")?;
+                }
+                Ok(())
+            }
             Self::Io(e) => write!(f, "{e}"),
-            #[cfg(feature = "json")]
+            #[cfg(feature = "serde-json")]
             Self::Serde(e) => write!(f, "{e}"),
             Self::MissingRoot => write!(f, "missing root widget"),
         }
@@ -41,7 +52,7 @@ impl From<crate::templates::error::Error> for Error {
     }
 }
 
-#[cfg(feature = "json")]
+#[cfg(feature = "serde-json")]
 impl From<serde_json::Error> for Error {
     fn from(e: serde_json::Error) -> Self {
         Self::Serde(e)
@@ -1,9 +1,13 @@
+use std::time::Instant;
+
 use crate::display::events::read;
 use crate::display::{ScreenPos, Size};
 use crate::templates::WidgetNode;
 
 use super::appstate::Receiver;
 use super::appstate::Sender;
+use super::record::EventLog;
+use super::timers::Timers;
 
 pub use crate::display::events::{
     CrossEvent, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
@@ -129,52 +133,107 @@ impl<T> Event<T> {
     }
 }
 
+impl<T> From<CrossEvent> for Event<T> {
+    fn from(event: CrossEvent) -> Self {
+        match event {
+            CrossEvent::Key(e) => Event::Key(e),
+            CrossEvent::Mouse(e) => Event::Mouse(e),
+            CrossEvent::Resize(w, h) => Event::Resize(Size::new(w as usize, h as usize)),
+        }
+    }
+}
+
 pub struct Events<T> {
     event_rx: Receiver<T>,
     tx: Sender<T>,
+    log: Option<EventLog>,
+    timers: Timers<T>,
 }
 
 impl<T: Send + Sync + 'static> Events<T> {
-    #[cfg(feature = "with-flume")]
+    #[cfg(feature = "flume")]
     pub fn bounded(cap: usize) -> Self {
         let (tx, rx) = flume::bounded(cap);
         Self::new(tx, rx)
     }
 
     pub fn unbounded() -> Self {
-        #[cfg(feature = "with-flume")]
+        #[cfg(feature = "flume")]
         let (tx, rx) = flume::unbounded();
-        #[cfg(not(feature = "with-flume"))]
+        #[cfg(not(feature = "flume"))]
         let (tx, rx) = std::sync::mpsc::channel();
         Self::new(tx, rx)
     }
 
+    /// Events that are not reading input from the terminal.
+    /// Only events sent through the [`Sender`] are received.
+    pub fn headless() -> Self {
+        #[cfg(feature = "flume")]
+        let (tx, event_rx) = flume::unbounded();
+        #[cfg(not(feature = "flume"))]
+        let (tx, event_rx) = std::sync::mpsc::channel();
+        Self { tx, event_rx, log: None, timers: Timers::new() }
+    }
+
     fn new(event_tx: Sender<T>, event_rx: Receiver<T>) -> Self {
         let tx = event_tx.clone();
         std::thread::spawn(move || events(tx));
-        Self { tx: event_tx, event_rx }
+        Self { tx: event_tx, event_rx, log: None, timers: Timers::new() }
+    }
+
+    /// Record all keyboard, mouse and resize events in an [`EventLog`].
+    pub fn record(mut self) -> Self {
+        self.log = Some(EventLog::new());
+        self
+    }
+
+    /// The recorded events, if recording.
+    pub fn event_log(&self) -> Option<&EventLog> {
+        self.log.as_ref()
     }
 
     pub fn sender(&self) -> Sender<T> {
         self.tx.clone()
     }
 
+    /// The timers, used to schedule messages.
+    pub fn timers(&self) -> Timers<T> {
+        self.timers.clone()
+    }
+
+    /// The next event.
+    ///
+    /// Timers that are due are fired before any other event.
+    /// If `blocking` is true this waits for the next event or until the next timer is due.
     pub fn next_event(&mut self, blocking: bool) -> Option<Event<T>> {
-        match blocking {
-            true => self.event_rx.recv().ok(),
-            false => self.event_rx.try_recv().ok(),
+        if let Some(message) = self.timers.pop_due(Instant::now()) {
+            return Some(Event::User(message));
+        }
+
+        let event = match (blocking, self.timers.next_due()) {
+            (true, Some(due)) => match self.event_rx.recv_timeout(due.saturating_duration_since(Instant::now())) {
+                Ok(event) => Some(event),
+                Err(_) => self.timers.pop_due(Instant::now()).map(Event::User),
+            },
+            (true, None) => self.event_rx.recv().ok(),
+            (false, _) => self.event_rx.try_recv().ok(),
+        };
+
+        if let Some(log) = self.log.as_mut() {
+            match event {
+                Some(Event::Key(e)) => log.push(CrossEvent::Key(e)),
+                Some(Event::Mouse(e)) => log.push(CrossEvent::Mouse(e)),
+                Some(Event::Resize(size)) => log.push(CrossEvent::Resize(size.width as u16, size.height as u16)),
+                _ => {}
+            }
         }
+
+        event
     }
 }
 
 fn events<T>(tx: Sender<T>) {
     while let Ok(event) = read() {
-        let payload = match event {
-            CrossEvent::Key(e) => Event::Key(e),
-            CrossEvent::Mouse(e) => Event::Mouse(e),
-            CrossEvent::Resize(w, h) => Event::Resize(Size::new(w as usize, h as usize)),
-        };
-
-        let _ = tx.send(payload);
+        let _ = tx.send(event.into());
     }
 }
@@ -1,27 +1,80 @@
 use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
 use std::time::Duration;
 
-use crate::display::Size;
 use crate::display::{
-    cursor, disable_raw_mode, enable_raw_mode, size, DisableMouseCapture, EnableMouseCapture, EnterAlternateScreen,
-    ExecutableCommand, LeaveAlternateScreen, QueueableCommand,
+    cursor, disable_raw_mode, enable_raw_mode, size, Clear, ClearType, DisableMouseCapture, EnableMouseCapture,
+    EnterAlternateScreen, ExecutableCommand, LeaveAlternateScreen, QueueableCommand,
 };
+use crate::display::{ColorDepth, Screen, Size};
 use crate::templates::parse;
 use crate::templates::DataCtx;
+use crate::templates::Stylesheet;
+use crate::templates::Theme;
 use crate::templates::WidgetLookup;
 use crate::widgets::WidgetContainer;
 
+pub mod crash;
 pub mod error;
 
 mod appstate;
+#[cfg(feature = "async")]
+mod async_runtime;
 mod events;
+pub mod record;
+mod timers;
 
 pub use appstate::{AppState, Run, Sender, UserModel, WaitFor};
+#[cfg(feature = "async")]
+pub use async_runtime::{AsyncRuntime, Spawner};
 pub use events::{CrossEvent, Event, Events, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+pub use timers::{TimerHandle, Timers};
 
 pub trait Output: Write {
     /// The size of the output.
     fn size(&self) -> Size;
+
+    /// The colour depth of the output.
+    fn color_depth(&self) -> ColorDepth {
+        ColorDepth::TrueColor
+    }
+
+    /// Render inline, below the cursor, rather than from the top left of the output.
+    fn is_inline(&self) -> bool {
+        false
+    }
+
+    /// Wrap every frame in a synchronized update, so the output draws the entire frame at once.
+    fn synchronized_output(&self) -> bool {
+        false
+    }
+
+    /// Render links as clickable hyperlinks.
+    fn hyperlinks(&self) -> bool {
+        true
+    }
+
+    /// The terminal was resized, called before the new size is read with [`Output::size`].
+    /// An output that doesn't query the terminal for its size (e.g. [`record::Headless`])
+    /// can follow the new size.
+    fn resize(&mut self, _size: Size) {}
+}
+
+/// The region of the terminal the runtime renders to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Viewport {
+    /// The entire terminal (or the alternate screen if enabled).
+    Fullscreen,
+    /// A fixed number of lines below the cursor, similar to `fzf --height`.
+    /// The alternate screen is never used in this mode.
+    Inline {
+        /// The number of lines to reserve.
+        height: u16,
+        /// Leave the last frame in the scrollback when the output is dropped,
+        /// otherwise the lines are cleared.
+        keep_last_frame: bool,
+    },
 }
 
 /// Configure the output.
@@ -33,10 +86,44 @@ pub struct OutputConfig {
     /// Render to an alternate screen.
     /// Once the config is dropped it will restore the main screen.
     pub alt_screen: bool,
+    /// The number of colours the terminal can display.
+    /// Defaults to [`ColorDepth::detect`], which honours `NO_COLOR`.
+    pub color_depth: ColorDepth,
+    /// The region of the terminal to render to.
+    pub viewport: Viewport,
+    /// Wrap every frame in a synchronized update (DEC mode 2026) to prevent tearing.
+    /// Defaults to [`Screen::detect_synchronized_output`](crate::display::Screen::detect_synchronized_output).
+    pub synchronized_output: bool,
+    /// Render links (e.g `span [link: "https://..."]`) as clickable hyperlinks (OSC 8).
+    /// If this is disabled links are rendered as plain text.
+    pub hyperlinks: bool,
+}
+
+impl Default for OutputConfig {
+    /// Raw mode on the alternate screen without the mouse.
+    /// The colour depth and synchronized output are detected from the environment.
+    fn default() -> Self {
+        Self {
+            alt_screen: true,
+            raw_mode: true,
+            enable_mouse: false,
+            color_depth: ColorDepth::detect(),
+            viewport: Viewport::Fullscreen,
+            synchronized_output: Screen::detect_synchronized_output(),
+            hyperlinks: true,
+        }
+    }
 }
 
 /// Stdout as [`Output`]
-pub struct Stdout(io::Stdout);
+pub struct Stdout {
+    inner: io::Stdout,
+    alt_screen: bool,
+    color_depth: ColorDepth,
+    viewport: Viewport,
+    synchronized_output: bool,
+    hyperlinks: bool,
+}
 
 impl Stdout {
     /// Create a new instance of [`self::Stdout`]
@@ -44,9 +131,10 @@ impl Stdout {
         let mut stdout = io::stdout();
         stdout.queue(cursor::Hide)?;
 
+        let alt_screen = config.raw_mode && config.alt_screen && config.viewport == Viewport::Fullscreen;
         if config.raw_mode {
             enable_raw_mode()?;
-            if config.alt_screen {
+            if alt_screen {
                 stdout.execute(EnterAlternateScreen)?;
             }
         }
@@ -56,30 +144,72 @@ impl Stdout {
         }
 
         stdout.flush()?;
-        Ok(Self(stdout))
+        Ok(Self {
+            inner: stdout,
+            alt_screen,
+            color_depth: config.color_depth,
+            viewport: config.viewport,
+            synchronized_output: config.synchronized_output,
+            hyperlinks: config.hyperlinks,
+        })
     }
 }
 
 impl Write for Stdout {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.0.write(buf)
+        self.inner.write(buf)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        self.0.flush()
+        self.inner.flush()
     }
 }
 
 impl Output for Stdout {
     fn size(&self) -> Size {
-        size().expect("failed to get terminal size").into()
+        let (width, height) = size().expect("failed to get terminal size");
+        match self.viewport {
+            Viewport::Fullscreen => (width, height).into(),
+            Viewport::Inline { height: lines, .. } => (width, lines.min(height)).into(),
+        }
+    }
+
+    fn color_depth(&self) -> ColorDepth {
+        self.color_depth
+    }
+
+    fn is_inline(&self) -> bool {
+        matches!(self.viewport, Viewport::Inline { .. })
+    }
+
+    fn synchronized_output(&self) -> bool {
+        self.synchronized_output
+    }
+
+    fn hyperlinks(&self) -> bool {
+        self.hyperlinks
     }
 }
 
 impl Drop for Stdout {
     fn drop(&mut self) {
+        // The cursor is at the top of the reserved lines
+        if let Viewport::Inline { height, keep_last_frame } = self.viewport {
+            if keep_last_frame {
+                if height > 1 {
+                    let _ = self.queue(cursor::MoveDown(height - 1));
+                }
+                let _ = self.write_all(b"\r\n");
+            } else {
+                let _ = self.queue(Clear(ClearType::FromCursorDown));
+            }
+        }
+
         let _ = disable_raw_mode();
-        let _ = self.execute(LeaveAlternateScreen);
+        // Leaving the alternate screen restores the cursor, which would move it off an inline frame
+        if self.alt_screen {
+            let _ = self.execute(LeaveAlternateScreen);
+        }
         let _ = self.execute(DisableMouseCapture);
         let _ = self.execute(cursor::Show);
     }
@@ -92,6 +222,13 @@ pub struct Runtime<T> {
     pub lookup: WidgetLookup,
     /// The amount of time between render / update calls.
     pub frame_time: Duration,
+    /// The initial theme, replacing any theme set on the data context.
+    /// Use [`DataCtx::set_theme`] to change the theme while running.
+    pub theme: Option<Theme>,
+    /// Styles available to the template, in addition to the styles declared in the template.
+    pub stylesheet: Stylesheet,
+    /// Save a [`crash::CrashReport`] to this path if the application panics.
+    pub crash_report: Option<PathBuf>,
     events: Events<T>,
 }
 
@@ -105,8 +242,15 @@ impl<T: Send + Sync + 'static> Runtime<T> {
     /// Create a new instance of the basic runtime.
     pub fn new() -> Self {
         let events = Events::unbounded();
-        let output_cfg = OutputConfig { alt_screen: true, raw_mode: true, enable_mouse: false };
-        Self { lookup: WidgetLookup::default(), events, output_cfg, frame_time: Duration::from_millis(20) }
+        Self {
+            lookup: WidgetLookup::default(),
+            events,
+            output_cfg: OutputConfig::default(),
+            frame_time: Duration::from_millis(20),
+            theme: None,
+            stylesheet: Stylesheet::default(),
+            crash_report: None,
+        }
     }
 
     /// Get an instance of the `Sender<T>`.
@@ -115,16 +259,25 @@ impl<T: Send + Sync + 'static> Runtime<T> {
         self.events.sender()
     }
 
+    /// Get the [`Timers`], used to schedule messages.
+    pub fn timers(&self) -> Timers<T> {
+        self.events.timers()
+    }
+
     /// Start the runtime with a custom user model.
     pub fn with_usermodel(
         self,
         template: impl AsRef<str>,
-        user_model: impl UserModel<Message = T>,
+        mut user_model: impl UserModel<Message = T>,
     ) -> error::Result<()> {
+        init_theme(self.theme.as_ref(), user_model.data());
+
         // -----------------------------------------------------------------------------
         //     - Output -
         // -----------------------------------------------------------------------------
         let output = Stdout::new(self.output_cfg)?;
+        // Restore the terminal if anything panics from here on
+        let _guard = crash::TerminalGuard::new(output.alt_screen);
 
         // -----------------------------------------------------------------------------
         //     - Nodes -
@@ -136,8 +289,15 @@ impl<T: Send + Sync + 'static> Runtime<T> {
         // -----------------------------------------------------------------------------
         let mut app =
             AppState::new(user_model, self.events, nodes, self.lookup, output, WaitFor::Timeout(self.frame_time))?;
+        if !self.stylesheet.is_empty() {
+            app.set_stylesheet(self.stylesheet)?;
+        }
 
-        while let Ok(Run::Continue) = app.wait_for() {}
+        let result = panic::catch_unwind(AssertUnwindSafe(|| while let Ok(Run::Continue) = app.wait_for() {}));
+
+        if let Err(payload) = result {
+            crash::report(app, payload, self.crash_report.as_deref());
+        }
 
         Ok(())
     }
@@ -153,6 +313,14 @@ impl<T: Send + Sync + 'static> Runtime<T> {
     }
 }
 
+// Set the runtime's theme, if it has one.
+// Otherwise a theme set with `DataCtx::set_theme` before starting is kept
+fn init_theme(theme: Option<&Theme>, data: &mut DataCtx) {
+    if let Some(theme) = theme {
+        data.set_theme(theme);
+    }
+}
+
 // -----------------------------------------------------------------------------
 //     - Dummy user model -
 // -----------------------------------------------------------------------------
@@ -181,3 +349,28 @@ where
         &mut self.data
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::display::Color;
+    use crate::widgets::{Path, Value};
+
+    #[test]
+    fn keep_theme_set_on_model() {
+        let primary = Path::from("$theme.primary");
+        let mut theme = Theme::new();
+        theme.insert("primary", Color::Red);
+        let mut data = DataCtx::empty();
+        data.set_theme(&theme);
+
+        let mut runtime = Runtime::<()>::new();
+        init_theme(runtime.theme.as_ref(), &mut data);
+        assert_eq!(data.by_path(&primary), Some(&Value::Color(Color::Red)));
+
+        theme.insert("primary", Color::Blue);
+        runtime.theme = Some(theme);
+        init_theme(runtime.theme.as_ref(), &mut data);
+        assert_eq!(data.by_path(&primary), Some(&Value::Color(Color::Blue)));
+    }
+}
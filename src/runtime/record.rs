@@ -0,0 +1,473 @@
+//! Record a running application.
+//!
+//! [`Recorder`] wraps an [`Output`] and records everything written to it,
+//! which can be saved as an [asciinema v2](https://docs.asciinema.org/manual/asciicast/v2/) `.cast` file.
+//!
+//! [`EventLog`] records the terminal input events (keyboard, mouse and resize)
+//! so a session can be replayed with [`super::AppState::replay`].
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::{CrossEvent, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind, Output};
+use crate::display::events::MouseButton;
+use crate::display::{ColorDepth, Size};
+
+// -----------------------------------------------------------------------------
+//     - Output recorder -
+// -----------------------------------------------------------------------------
+/// An [`Output`] that records every chunk of bytes written to the inner output.
+///
+/// A chunk is everything written between two calls to `flush`
+/// (the [`crate::display::Screen`] flushes once per frame).
+pub struct Recorder<O> {
+    inner: O,
+    size: Size,
+    start: Instant,
+    timestamp: u64,
+    pending: Vec<u8>,
+    chunks: Vec<(Duration, Vec<u8>)>,
+    resizes: Vec<(Duration, Size)>,
+}
+
+impl<O: Output> Recorder<O> {
+    /// Start recording the output.
+    pub fn new(inner: O) -> Self {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|t| t.as_secs()).unwrap_or(0);
+        Self {
+            size: inner.size(),
+            inner,
+            start: Instant::now(),
+            timestamp,
+            pending: vec![],
+            chunks: vec![],
+            resizes: vec![],
+        }
+    }
+
+    /// The recorded chunks and the time they were written, relative to the start of the recording.
+    pub fn chunks(&self) -> &[(Duration, Vec<u8>)] {
+        &self.chunks
+    }
+
+    /// Write the recording as an asciinema v2 `.cast` file.
+    pub fn write_cast(&self, mut w: impl Write) -> io::Result<()> {
+        writeln!(
+            w,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}}}",
+            self.size.width, self.size.height, self.timestamp
+        )?;
+
+        // Output and resize events, in the order they happened
+        let mut resizes = self.resizes.iter().peekable();
+        for (time, bytes) in &self.chunks {
+            while let Some((resize_time, size)) = resizes.next_if(|(resize_time, _)| resize_time <= time) {
+                write_resize(&mut w, resize_time, size)?;
+            }
+            let data = json_escape(&String::from_utf8_lossy(bytes));
+            writeln!(w, "[{:.6}, \"o\", \"{data}\"]", time.as_secs_f64())?;
+        }
+
+        for (time, size) in resizes {
+            write_resize(&mut w, time, size)?;
+        }
+
+        Ok(())
+    }
+
+    /// Stop recording and return the inner output.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+}
+
+impl<O: Output> Write for Recorder<O> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.pending.extend(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            let chunk = std::mem::take(&mut self.pending);
+            self.chunks.push((self.start.elapsed(), chunk));
+        }
+        self.inner.flush()
+    }
+}
+
+impl<O: Output> Output for Recorder<O> {
+    fn size(&self) -> Size {
+        self.inner.size()
+    }
+
+    fn color_depth(&self) -> ColorDepth {
+        self.inner.color_depth()
+    }
+
+    fn is_inline(&self) -> bool {
+        self.inner.is_inline()
+    }
+
+    fn synchronized_output(&self) -> bool {
+        self.inner.synchronized_output()
+    }
+
+    fn hyperlinks(&self) -> bool {
+        self.inner.hyperlinks()
+    }
+
+    fn resize(&mut self, size: Size) {
+        self.inner.resize(size);
+        self.resizes.push((self.start.elapsed(), size));
+    }
+}
+
+fn write_resize(mut w: impl Write, time: &Duration, size: &Size) -> io::Result<()> {
+    writeln!(w, "[{:.6}, \"r\", \"{}x{}\"]", time.as_secs_f64(), size.width, size.height)
+}
+
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// -----------------------------------------------------------------------------
+//     - Headless output -
+// -----------------------------------------------------------------------------
+/// An [`Output`] that writes to memory instead of a terminal.
+/// The size only changes on a resize event.
+/// Useful for replaying a session or running an `AppState` in tests.
+pub struct Headless {
+    size: Size,
+    /// Everything written to the output.
+    pub written: Vec<u8>,
+}
+
+impl Headless {
+    /// Create a new headless output of a given size.
+    pub fn new(size: impl Into<Size>) -> Self {
+        Self { size: size.into(), written: vec![] }
+    }
+}
+
+impl Write for Headless {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Output for Headless {
+    fn size(&self) -> Size {
+        self.size
+    }
+
+    fn resize(&mut self, size: Size) {
+        self.size = size;
+    }
+}
+
+// -----------------------------------------------------------------------------
+//     - Event log -
+// -----------------------------------------------------------------------------
+/// A log of terminal input events and the time they were received,
+/// relative to the start of the log.
+///
+/// The log can be saved and loaded as text, with one event per line:
+///
+/// ```text
+/// 120 key 0 c:a
+/// 250 mouse 0 4 2 down:left
+/// 300 resize 80 24
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    start: Instant,
+    events: Vec<(Duration, CrossEvent)>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventLog {
+    /// Create an empty event log.
+    pub fn new() -> Self {
+        Self { start: Instant::now(), events: vec![] }
+    }
+
+    /// Record an event.
+    pub fn push(&mut self, event: CrossEvent) {
+        self.events.push((self.start.elapsed(), event));
+    }
+
+    /// The recorded events.
+    pub fn events(&self) -> &[(Duration, CrossEvent)] {
+        &self.events
+    }
+
+    /// Save the event log.
+    pub fn write(&self, mut w: impl Write) -> io::Result<()> {
+        for (time, event) in &self.events {
+            let ms = time.as_millis();
+            match event {
+                CrossEvent::Key(KeyEvent { code, modifiers }) => {
+                    writeln!(w, "{ms} key {} {}", modifiers.bits(), key_code_to_str(code))?
+                }
+                CrossEvent::Mouse(MouseEvent { kind, column, row, modifiers }) => {
+                    writeln!(w, "{ms} mouse {} {column} {row} {}", modifiers.bits(), mouse_kind_to_str(kind))?
+                }
+                CrossEvent::Resize(width, height) => writeln!(w, "{ms} resize {width} {height}")?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Load an event log previously saved with [`EventLog::write`].
+    pub fn read(r: impl BufRead) -> io::Result<Self> {
+        let mut events = vec![];
+        for line in r.lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            events.push(parse_event(&line).ok_or_else(|| invalid_data(&line))?);
+        }
+        Ok(Self { start: Instant::now(), events })
+    }
+}
+
+fn invalid_data(line: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid event: {line}"))
+}
+
+fn parse_event(line: &str) -> Option<(Duration, CrossEvent)> {
+    let mut parts = line.splitn(3, ' ');
+    let time = Duration::from_millis(parts.next()?.parse().ok()?);
+    let kind = parts.next()?;
+    let rest = parts.next()?;
+
+    let event = match kind {
+        "key" => {
+            // The key code is last as a char can be a space
+            let (modifiers, code) = rest.split_once(' ')?;
+            let modifiers = KeyModifiers::from_bits(modifiers.parse().ok()?)?;
+            CrossEvent::Key(KeyEvent { code: str_to_key_code(code)?, modifiers })
+        }
+        "mouse" => {
+            let mut parts = rest.split(' ');
+            let modifiers = KeyModifiers::from_bits(parts.next()?.parse().ok()?)?;
+            let column = parts.next()?.parse().ok()?;
+            let row = parts.next()?.parse().ok()?;
+            let kind = str_to_mouse_kind(parts.next()?)?;
+            CrossEvent::Mouse(MouseEvent { kind, column, row, modifiers })
+        }
+        "resize" => {
+            let (width, height) = rest.split_once(' ')?;
+            CrossEvent::Resize(width.parse().ok()?, height.parse().ok()?)
+        }
+        _ => return None,
+    };
+
+    Some((time, event))
+}
+
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::Backspace, "backspace"),
+    (KeyCode::Enter, "enter"),
+    (KeyCode::Left, "left"),
+    (KeyCode::Right, "right"),
+    (KeyCode::Up, "up"),
+    (KeyCode::Down, "down"),
+    (KeyCode::Home, "home"),
+    (KeyCode::End, "end"),
+    (KeyCode::PageUp, "pageup"),
+    (KeyCode::PageDown, "pagedown"),
+    (KeyCode::Tab, "tab"),
+    (KeyCode::BackTab, "backtab"),
+    (KeyCode::Delete, "delete"),
+    (KeyCode::Insert, "insert"),
+    (KeyCode::Null, "null"),
+    (KeyCode::Esc, "esc"),
+];
+
+fn key_code_to_str(code: &KeyCode) -> String {
+    match code {
+        KeyCode::Char(c) => format!("c:{c}"),
+        KeyCode::F(n) => format!("f:{n}"),
+        code => KEY_NAMES.iter().find(|(c, _)| c == code).map(|(_, name)| name.to_string()).unwrap_or_default(),
+    }
+}
+
+fn str_to_key_code(s: &str) -> Option<KeyCode> {
+    match s.split_once(':') {
+        Some(("c", c)) => c.chars().next().map(KeyCode::Char),
+        Some(("f", n)) => n.parse().ok().map(KeyCode::F),
+        _ => KEY_NAMES.iter().find(|(_, name)| *name == s).map(|(code, _)| *code),
+    }
+}
+
+fn mouse_kind_to_str(kind: &MouseEventKind) -> String {
+    let button = |btn: &MouseButton| match btn {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+    };
+
+    match kind {
+        MouseEventKind::Down(btn) => format!("down:{}", button(btn)),
+        MouseEventKind::Up(btn) => format!("up:{}", button(btn)),
+        MouseEventKind::Drag(btn) => format!("drag:{}", button(btn)),
+        MouseEventKind::Moved => "moved".to_string(),
+        MouseEventKind::ScrollDown => "scrolldown".to_string(),
+        MouseEventKind::ScrollUp => "scrollup".to_string(),
+    }
+}
+
+fn str_to_mouse_kind(s: &str) -> Option<MouseEventKind> {
+    let button = |s: &str| match s {
+        "left" => Some(MouseButton::Left),
+        "right" => Some(MouseButton::Right),
+        "middle" => Some(MouseButton::Middle),
+        _ => None,
+    };
+
+    match s.split_once(':') {
+        Some(("down", btn)) => button(btn).map(MouseEventKind::Down),
+        Some(("up", btn)) => button(btn).map(MouseEventKind::Up),
+        Some(("drag", btn)) => button(btn).map(MouseEventKind::Drag),
+        _ => match s {
+            "moved" => Some(MouseEventKind::Moved),
+            "scrolldown" => Some(MouseEventKind::ScrollDown),
+            "scrollup" => Some(MouseEventKind::ScrollUp),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn record_cast() {
+        let mut recorder = Recorder::new(Headless::new((10u16, 2)));
+        recorder.write_all(b"hello").unwrap();
+        recorder.write_all(b" \"world\"\n").unwrap();
+        recorder.flush().unwrap();
+        // Nothing written, no chunk
+        recorder.flush().unwrap();
+        recorder.resize(Size::new(20, 4));
+        recorder.write_all(b"!").unwrap();
+        recorder.flush().unwrap();
+
+        assert_eq!(recorder.chunks().len(), 2);
+        assert_eq!(recorder.size(), Size::new(20, 4));
+
+        let mut cast = vec![];
+        recorder.write_cast(&mut cast).unwrap();
+        let cast = String::from_utf8(cast).unwrap();
+        let mut lines = cast.lines();
+        assert!(lines.next().unwrap().starts_with("{\"version\": 2, \"width\": 10, \"height\": 2, \"timestamp\": "));
+        assert!(lines.next().unwrap().ends_with(", \"o\", \"hello \\\"world\\\"\\n\"]"));
+        assert!(lines.next().unwrap().ends_with(", \"r\", \"20x4\"]"));
+        assert!(lines.next().unwrap().ends_with(", \"o\", \"!\"]"));
+        assert!(lines.next().is_none());
+
+        assert_eq!(recorder.into_inner().written, b"hello \"world\"\n!");
+    }
+
+    #[test]
+    fn event_log_round_trip() {
+        let mut log = EventLog::new();
+        log.push(CrossEvent::Key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::CONTROL)));
+        log.push(CrossEvent::Key(KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE)));
+        log.push(CrossEvent::Key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::SHIFT | KeyModifiers::ALT)));
+        log.push(CrossEvent::Mouse(MouseEvent {
+            kind: MouseEventKind::Drag(MouseButton::Middle),
+            column: 4,
+            row: 2,
+            modifiers: KeyModifiers::NONE,
+        }));
+        log.push(CrossEvent::Resize(80, 24));
+
+        let mut saved = vec![];
+        log.write(&mut saved).unwrap();
+        let loaded = EventLog::read(saved.as_slice()).unwrap();
+
+        let events = |log: &EventLog| log.events().iter().map(|(_, e)| *e).collect::<Vec<_>>();
+        assert_eq!(events(&log), events(&loaded));
+    }
+
+    #[test]
+    fn replay() {
+        use crate::runtime::{AppState, Event, Events, UserModel, WaitFor};
+        use crate::templates::{parse, DataCtx, WidgetLookup};
+        use crate::widgets::WidgetContainer;
+
+        struct Model(DataCtx);
+
+        impl UserModel for Model {
+            type Message = ();
+
+            fn event(&mut self, event: Event<()>, _: &mut WidgetContainer) {
+                if let Some(KeyCode::Char(c)) = event.get_keycode() {
+                    if let Some(text) = self.0.get_string_mut("text") {
+                        text.push(c);
+                    }
+                }
+            }
+
+            fn data(&mut self) -> &mut DataCtx {
+                &mut self.0
+            }
+        }
+
+        let mut log = EventLog::new();
+        log.push(CrossEvent::Key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE)));
+        log.push(CrossEvent::Key(KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE)));
+
+        let mut data = DataCtx::empty();
+        data.insert("text", String::new());
+        let nodes = parse("text: \"> {{ text }}\"").unwrap();
+        let output = Recorder::new(Headless::new((10u16, 1)));
+        let mut app =
+            AppState::new(Model(data), Events::headless(), nodes, WidgetLookup::default(), output, WaitFor::Input)
+                .unwrap();
+
+        app.replay(&log).unwrap();
+        assert_eq!(app.screen().rendered().to_plain_text(), "> hi");
+        assert!(!app.output().chunks().is_empty());
+
+        // The output follows the size of a replayed resize
+        let mut log = EventLog::new();
+        log.push(CrossEvent::Resize(4, 1));
+        app.replay(&log).unwrap();
+        assert_eq!(app.output().size(), Size::new(4, 1));
+        assert_eq!(app.screen().rendered().size(), Size::new(4, 1));
+        assert_eq!(app.screen().rendered().to_plain_text(), "> hi");
+    }
+
+    #[test]
+    fn invalid_event_log() {
+        assert!(EventLog::read("12 key 0 nope".as_bytes()).is_err());
+        assert!(EventLog::read("12 dance".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Repeating timers fire at most this often
+const MIN_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Cancel a scheduled timer.
+///
+/// Dropping the handle does not cancel the timer.
+#[derive(Debug, Clone, Default)]
+pub struct TimerHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TimerHandle {
+    /// Cancel the timer. A cancelled timer will not fire again.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the timer was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+enum Message<T> {
+    Once(Option<T>),
+    Every(Duration, Box<dyn FnMut() -> T + Send>),
+}
+
+struct Timer<T> {
+    due: Instant,
+    message: Message<T>,
+    handle: TimerHandle,
+}
+
+/// Schedule messages that are sent to the user model as [`super::Event::User`].
+///
+/// Timers are fired by the event loop (see [`super::Events::next_event`]),
+/// so they are only as precise as the loop: with [`super::WaitFor::Timeout`] a
+/// timer can fire up to one frame late.
+/// With [`super::WaitFor::Input`] the loop wakes up when the next timer is due,
+/// however a timer scheduled from another thread while the loop is waiting
+/// won't be noticed until the next event.
+pub struct Timers<T> {
+    inner: Arc<Mutex<Vec<Timer<T>>>>,
+}
+
+impl<T> Clone for Timers<T> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+impl<T: Send + 'static> Timers<T> {
+    pub(crate) fn new() -> Self {
+        Self { inner: Arc::new(Mutex::new(vec![])) }
+    }
+
+    /// Send the message once, after the given duration.
+    pub fn schedule_once(&self, after: Duration, message: T) -> TimerHandle {
+        self.push(Instant::now() + after, Message::Once(Some(message)))
+    }
+
+    /// Send the message every `interval`, starting one interval from now.
+    pub fn schedule_every(&self, interval: Duration, message: T) -> TimerHandle
+    where
+        T: Clone,
+    {
+        let interval = interval.max(MIN_INTERVAL);
+        self.push(Instant::now() + interval, Message::Every(interval, Box::new(move || message.clone())))
+    }
+
+    fn push(&self, due: Instant, message: Message<T>) -> TimerHandle {
+        let handle = TimerHandle::default();
+        self.lock().push(Timer { due, message, handle: handle.clone() });
+        handle
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Timer<T>>> {
+        // A panic while holding the lock can't leave the timers in an invalid state
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The time the next timer is due, if there are any timers.
+    pub(crate) fn next_due(&self) -> Option<Instant> {
+        self.lock().iter().filter(|t| !t.handle.is_cancelled()).map(|t| t.due).min()
+    }
+
+    /// Take the message of the first timer that is due at `now`.
+    /// Repeating timers are rescheduled, skipping any intervals that were missed.
+    pub(crate) fn pop_due(&self, now: Instant) -> Option<T> {
+        let mut timers = self.lock();
+        timers.retain(|t| !t.handle.is_cancelled());
+
+        let index = timers.iter().enumerate().filter(|(_, t)| t.due <= now).min_by_key(|(_, t)| t.due)?.0;
+
+        let timer = &mut timers[index];
+        match timer.message {
+            Message::Once(ref mut message) => {
+                let message = message.take();
+                timers.remove(index);
+                message
+            }
+            Message::Every(interval, ref mut message) => {
+                timer.due += interval;
+                if timer.due <= now {
+                    timer.due = now + interval;
+                }
+                Some(message())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn once() {
+        let timers = Timers::new();
+        let now = Instant::now();
+        timers.schedule_once(Duration::from_millis(20), "b");
+        timers.schedule_once(Duration::from_millis(10), "a");
+
+        assert!(timers.pop_due(now).is_none());
+        assert!(timers.next_due().unwrap() <= now + Duration::from_millis(20));
+
+        let later = now + Duration::from_secs(1);
+        assert_eq!(timers.pop_due(later), Some("a"));
+        assert_eq!(timers.pop_due(later), Some("b"));
+        assert_eq!(timers.pop_due(later), None);
+        assert!(timers.next_due().is_none());
+    }
+
+    #[test]
+    fn every() {
+        let timers = Timers::new();
+        let handle = timers.schedule_every(Duration::from_secs(1), "tick");
+        let due = timers.next_due().unwrap();
+
+        assert_eq!(timers.pop_due(due), Some("tick"));
+        assert_eq!(timers.next_due(), Some(due + Duration::from_secs(1)));
+
+        // Missed intervals are skipped
+        let late = due + Duration::from_millis(3500);
+        assert_eq!(timers.pop_due(late), Some("tick"));
+        assert_eq!(timers.pop_due(late), None);
+        assert_eq!(timers.next_due(), Some(late + Duration::from_secs(1)));
+
+        handle.cancel();
+        assert!(handle.is_cancelled());
+        assert!(timers.next_due().is_none());
+        assert_eq!(timers.pop_due(late + Duration::from_secs(10)), None);
+    }
+
+    #[test]
+    fn wake_up_for_timer() {
+        use crate::runtime::{Event, Events};
+
+        let mut events = Events::<&str>::headless();
+        events.timers().schedule_once(Duration::from_millis(5), "wake");
+
+        // Without the timer this would block forever
+        let event = events.next_event(true).and_then(Event::user);
+        assert_eq!(event, Some("wake"));
+    }
+}
@@ -1,27 +1,125 @@
 use std::collections::hash_map::{Entry, HashMap};
+use std::fmt;
+use std::sync::Arc;
 
 use crate::display::Color;
-use crate::widgets::{Align, BorderStyle, Direction, Display, Number, Path, Sides, Value, Wrap};
+use crate::widgets::{
+    fields, snapshot, Align, Attributes, BorderStyle, Direction, Display, Number, Path, Sides, Value, Wrap,
+    STYLE_FIELDS,
+};
 
 use super::error::Result;
+use super::history::History;
+use super::theme::{Theme, THEME_KEY};
 use super::WidgetNode;
 
+// Keys written by the runtime rather than the user model:
+// the theme, and the metrics if the runtime writes them
+fn is_internal(key: &str) -> bool {
+    key == THEME_KEY || (cfg!(feature = "metrics") && key == "context")
+}
+
 /// Caching includes
 #[derive(Debug, Default)]
 pub struct IncludeCache(HashMap<String, Vec<WidgetNode>>);
 
+/// Named styles declared in a template:
+///
+/// ```text
+/// style [foreground: yellow, bold: true]: "warning"
+///
+/// text [class: "warning"]: "careful now"
+/// ```
+///
+/// A node can have more than one class by separating the names with a space.
+/// Attributes set directly on the node takes precedence over the class.
+/// Only the attributes that make up the style of a widget (colours, `bold`, `underline-style` etc.)
+/// are applied, anything else in a style is ignored.
+///
+/// Styles can also be kept in a separate file and loaded with [`Stylesheet::load`],
+/// or created in code, and given to the runtime so they are available to every template.
+#[derive(Debug, Default, Clone)]
+pub struct Stylesheet(HashMap<String, Attributes>);
+
+impl Stylesheet {
+    /// Parse a stylesheet that only contains style declarations.
+    ///
+    /// Values bound to data (e.g `{{ colour }}`, or `$primary` from the theme) are looked up
+    /// when the style is applied to a node.
+    pub fn parse(src: &str) -> Result<Self> {
+        let widget_nodes = super::parse(src)?;
+        super::nodes::to_stylesheet(widget_nodes)
+    }
+
+    /// Load a stylesheet from a file, see [`Stylesheet::parse`].
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let src = std::fs::read_to_string(path)?;
+        Self::parse(&src)
+    }
+
+    /// Add a named style.
+    /// If a style already exists with the same name it's replaced.
+    pub fn insert(&mut self, name: impl Into<String>, attributes: Attributes) {
+        self.0.insert(name.into(), attributes);
+    }
+
+    /// Get a style by name.
+    pub fn get(&self, name: &str) -> Option<&Attributes> {
+        self.0.get(name)
+    }
+
+    /// Returns `true` if the stylesheet has no styles.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn styles(&self) -> impl Iterator<Item = &Attributes> {
+        self.0.values()
+    }
+
+    // Apply the style attributes of all the classes to the attributes,
+    // without replacing existing attributes
+    pub(crate) fn apply(&self, attributes: &mut Attributes) {
+        let classes = match attributes.get_string(fields::CLASS) {
+            Some(classes) => classes,
+            None => return,
+        };
+
+        for class in classes.split_whitespace() {
+            let style = match self.get(class) {
+                Some(style) => style,
+                None => continue,
+            };
+
+            for (k, v) in style {
+                if STYLE_FIELDS.contains(&k.as_str()) && !attributes.has(k) {
+                    attributes.set(k, v.clone());
+                }
+            }
+        }
+    }
+}
+
 /// Track the include depth and maintain the include cache to prevent
 /// multiple reads from disk.
 #[derive(Debug)]
 pub struct NodeCtx<'cache> {
     include_cache: &'cache mut IncludeCache,
     pub(crate) include_depth: usize,
+    pub(crate) stylesheet: Stylesheet,
 }
 
 impl<'cache> NodeCtx<'cache> {
     /// Create a new instance of a `NodeCtx`.
     pub fn new(include_cache: &'cache mut IncludeCache) -> Self {
-        Self { include_cache, include_depth: 0 }
+        Self { include_cache, include_depth: 0, stylesheet: Stylesheet::default() }
+    }
+
+    /// Start with the styles in the stylesheet.
+    /// Styles declared in the template are added to these.
+    pub fn with_stylesheet(mut self, stylesheet: Stylesheet) -> Self {
+        self.stylesheet = stylesheet;
+        self
     }
 
     pub(crate) fn includes(&mut self, path: String) -> Result<Vec<WidgetNode>> {
@@ -56,6 +154,11 @@ impl<'ctx> SubContext<'ctx> {
         self.sub.insert(key, value);
     }
 
+    /// Clone self into a new sub context, without adding any values.
+    pub fn sub_empty(&self) -> Self {
+        Self { inner: self.inner, sub: self.sub.clone() }
+    }
+
     /// Clone self and generate a new sub context with the new values
     pub fn sub(&self, key: &str, value: Value) -> Self {
         let mut sub = self.sub.clone();
@@ -72,13 +175,64 @@ impl<'ctx> SubContext<'ctx> {
     }
 }
 
+// A value computed from other values in a `DataCtx`
+#[derive(Clone)]
+struct Computed {
+    key: String,
+    inputs: Vec<Path>,
+    f: Arc<dyn Fn(&DataCtx) -> Value + Send + Sync>,
+    // Changes made before the value was computed the first time are already accounted for
+    since: usize,
+}
+
+impl fmt::Debug for Computed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Computed").field("key", &self.key).field("inputs", &self.inputs).finish()
+    }
+}
+
+impl PartialEq for Computed {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.inputs == other.inputs && Arc::ptr_eq(&self.f, &other.f)
+    }
+}
+
+// Look up a value by path
+pub(super) fn lookup<'a>(values: &'a HashMap<String, Value>, path: &Path) -> Option<&'a Value> {
+    let value = values.get(&path.name)?;
+    match &path.child {
+        Some(child) => Value::lookup(child, value),
+        None => Some(value),
+    }
+}
+
+// Look up a value without adding a diff
+pub(super) fn lookup_mut<'a>(values: &'a mut HashMap<String, Value>, path: &Path) -> Option<&'a mut Value> {
+    let value = values.get_mut(&path.name)?;
+    match &path.child {
+        Some(child) => Value::lookup_mut(child, value),
+        None => Some(value),
+    }
+}
+
+// Split the path into the parent path and the last name,
+// or `None` if the path has no parent.
+pub(super) fn split_last(path: &Path) -> Option<(Path, String)> {
+    let child = path.child.as_ref()?;
+    match split_last(child) {
+        Some((parent, name)) => Some((Path { name: path.name.clone(), child: Some(Box::new(parent)) }, name)),
+        None => Some((Path::new(&path.name), child.name.clone())),
+    }
+}
+
 macro_rules! mut_ref_push_diff {
     ($fn:ident, $ret:ty, $variant:ident) => {
         /// Get a mutable reference to a `$ret`
         pub fn $fn(&mut self, key: &str) -> Option<&mut $ret> {
+            self.record(&Path::new(key));
             match self.values.get_mut(key)? {
                 Value::$variant(value) => {
-                    self.diff.push(key.into());
+                    self.diff.push(Path::new(key));
                     Some(value)
                 }
                 _ => None,
@@ -100,16 +254,33 @@ macro_rules! get_ref {
 }
 
 /// Contain values that are available inside templates.
+///
+/// Changes are tracked by path, so changing a value inside a map or a list
+/// (see [`DataCtx::set_path`]) only updates the parts of the template that use that value.
+///
+/// ```
+/// use anathema::templates::DataCtx;
+/// use anathema::widgets::{Path, Value};
+///
+/// let mut ctx = DataCtx::empty();
+/// ctx.insert("user", Value::Map(Default::default()));
+/// ctx.set_path(&Path::from("user.name"), "Bill");
+/// ctx.list_push(&Path::from("user.friends"), "Ted");
+///
+/// assert_eq!(ctx.by_path(&Path::from("user.name")), Some(&Value::from("Bill")));
+/// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct DataCtx {
     values: HashMap<String, Value>,
-    diff: Vec<String>,
+    diff: Vec<Path>,
+    computed: Vec<Computed>,
+    history: Option<History>,
 }
 
 impl DataCtx {
     /// Create a new data context with a given key / value.
     pub fn with_value(key: &str, value: impl Into<Value>) -> Self {
-        let mut ctx = Self { values: HashMap::new(), diff: Vec::new() };
+        let mut ctx = Self::empty();
         ctx.insert(key, value.into());
         ctx
     }
@@ -133,28 +304,147 @@ impl DataCtx {
 
     /// Find a value by path.
     pub fn by_path(&self, path: &Path) -> Option<&Value> {
-        let value = self.get(path.name.as_str())?;
-        match value {
-            Value::Map(_) if path.child.is_none() => Some(value),
-            Value::Map(_) => {
-                let path = path.child.as_ref()?;
-                Value::lookup(path, value)
+        lookup(&self.values, path)
+    }
+
+    /// Get a mutable reference to a value by path.
+    /// This will add the path to the diff lookup.
+    pub fn by_path_mut(&mut self, path: &Path) -> Option<&mut Value> {
+        self.record(path);
+        let value = lookup_mut(&mut self.values, path)?;
+        self.diff.push(path.clone());
+        Some(value)
+    }
+
+    /// Set a value by path.
+    /// A value in a map is inserted if it doesn't exist, however the map itself has to exist.
+    /// A value in a list replaces the value at the index.
+    ///
+    /// Returns `false` if there is no map or list to set the value in.
+    /// This will add the path to the diff lookup.
+    pub fn set_path(&mut self, path: &Path, value: impl Into<Value>) -> bool {
+        self.record(path);
+        let value = value.into();
+        let (parent, name) = match split_last(path) {
+            Some(split) => split,
+            None => {
+                self.insert(&path.name, value);
+                return true;
+            }
+        };
+
+        let set = match lookup_mut(&mut self.values, &parent) {
+            Some(Value::Map(map)) => {
+                map.insert(name, value);
+                true
             }
-            _ => Some(value),
+            Some(Value::List(list)) => match name.parse::<usize>().ok().and_then(|index| list.get_mut(index)) {
+                Some(old) => {
+                    *old = value;
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        };
+
+        if set {
+            self.diff.push(path.clone());
+        }
+        set
+    }
+
+    /// Push a value to the end of a list.
+    /// If there is no value at the path a new list is created,
+    /// as long as the parent exists.
+    ///
+    /// Returns `false` if the value at the path is not a list.
+    /// This will add the path to the diff lookup.
+    pub fn list_push(&mut self, path: &Path, value: impl Into<Value>) -> bool {
+        if lookup_mut(&mut self.values, path).is_none() {
+            return self.set_path(path, Value::List(vec![value.into()]));
+        }
+        self.list_mut(path).map(|list| list.push(value.into())).is_some()
+    }
+
+    /// Insert a value into a list at the index, shifting all values after it.
+    ///
+    /// Returns `false` if the value at the path is not a list or the index is out of bounds.
+    /// This will add the path to the diff lookup.
+    pub fn list_insert(&mut self, path: &Path, index: usize, value: impl Into<Value>) -> bool {
+        match self.list_len(path) {
+            Some(len) if index <= len => self.list_mut(path).map(|list| list.insert(index, value.into())).is_some(),
+            _ => false,
+        }
+    }
+
+    /// Remove the value at the index from a list.
+    ///
+    /// Returns `None` if the value at the path is not a list or the index is out of bounds.
+    /// This will add the path to the diff lookup.
+    pub fn list_remove(&mut self, path: &Path, index: usize) -> Option<Value> {
+        match self.list_len(path) {
+            Some(len) if index < len => self.list_mut(path).map(|list| list.remove(index)),
+            _ => None,
+        }
+    }
+
+    // The length of a list, checked before changing the list
+    // so an index out of bounds doesn't add a diff
+    fn list_len(&self, path: &Path) -> Option<usize> {
+        match lookup(&self.values, path)? {
+            Value::List(list) => Some(list.len()),
+            _ => None,
+        }
+    }
+
+    // Get a mutable list, adding a diff only if the value is a list
+    fn list_mut(&mut self, path: &Path) -> Option<&mut Vec<Value>> {
+        match lookup_mut(&mut self.values, path)? {
+            Value::List(_) => {}
+            _ => return None,
+        }
+        match self.by_path_mut(path)? {
+            Value::List(list) => Some(list),
+            _ => None,
         }
     }
 
     /// Create an empty data context.
     pub fn empty() -> Self {
-        DataCtx { values: HashMap::new(), diff: Vec::new() }
+        DataCtx { values: HashMap::new(), diff: Vec::new(), computed: Vec::new(), history: None }
     }
 
     /// Insert a key / value into the data context.
     /// This will add the key to the diff lookup.
     pub fn insert(&mut self, key: &str, value: impl Into<Value>) {
+        self.record(&Path::new(key));
         let value = value.into();
         self.set(key, value);
-        self.diff.push(key.into());
+        self.diff.push(Path::new(key));
+    }
+
+    /// Serialize a value and insert it into the data context.
+    /// This will add the key to the diff lookup.
+    ///
+    /// ```
+    /// use anathema::templates::DataCtx;
+    ///
+    /// #[derive(serde::Serialize)]
+    /// struct State {
+    ///     counter: u32,
+    /// }
+    ///
+    /// let mut ctx = DataCtx::empty();
+    /// ctx.insert_serialize("state", &State { counter: 1 }).unwrap();
+    /// ```
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn insert_serialize<T: serde::Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<()> {
+        let value = crate::widgets::serialize::to_value(value)?;
+        self.insert(key, value);
+        Ok(())
     }
 
     /// Set a value without generating a diff insert.
@@ -165,24 +455,242 @@ impl DataCtx {
 
     /// Remove a value.
     /// This does not generate a diff.
+    ///
+    /// Removing a computed value removes the computation as well.
     pub fn remove(&mut self, key: &str) {
         self.values.remove(key);
+        self.computed.retain(|computed| computed.key != key);
+    }
+
+    /// Insert a value computed from other values in the context.
+    ///
+    /// The value is computed straight away, and then again whenever any of the `inputs`
+    /// is part of the diff, once the changes are taken (i.e. once per rebuild, no matter
+    /// how many times the inputs changed).
+    /// Until then the previous value is returned.
+    ///
+    /// A computed value can be used as an input to another computed value,
+    /// as long as it's inserted first.
+    /// Inserting a computed value with the same key as an existing one replaces it.
+    ///
+    /// ```
+    /// use anathema::templates::DataCtx;
+    /// use anathema::widgets::Value;
+    ///
+    /// let mut ctx = DataCtx::with_value("items", vec![1u64, 2, 3]);
+    /// ctx.insert_computed("count", &["items"], |ctx| match ctx.get("items") {
+    ///     Some(Value::List(items)) => items.len() as u64,
+    ///     _ => 0,
+    /// });
+    /// assert_eq!(ctx.get_u64("count"), Some(&3));
+    ///
+    /// ctx.list_push(&"items".into(), 4u64);
+    /// let _ = ctx.take_changes();
+    /// assert_eq!(ctx.get_u64("count"), Some(&4));
+    /// ```
+    pub fn insert_computed<F, V>(&mut self, key: &str, inputs: &[&str], f: F)
+    where
+        F: Fn(&DataCtx) -> V + Send + Sync + 'static,
+        V: Into<Value>,
+    {
+        let computed = Computed {
+            key: key.to_string(),
+            inputs: inputs.iter().copied().map(Path::from).collect(),
+            f: Arc::new(move |ctx| f(ctx).into()),
+            since: 0,
+        };
+        // Computed values are not part of the history, as they are computed again after an undo
+        let value = (computed.f)(self);
+        self.values.insert(key.to_string(), value);
+        self.diff.push(Path::new(key));
+
+        self.computed.retain(|computed| computed.key != key);
+        self.computed.push(Computed { since: self.diff.len(), ..computed });
+    }
+
+    // Re-evaluate every computed value with a changed input.
+    // The computed keys are added to the diff so anything depending on them is updated too.
+    fn compute(&mut self) {
+        for index in 0..self.computed.len() {
+            let computed = &self.computed[index];
+            let changes = &self.diff[computed.since..];
+            if computed.inputs.iter().any(|input| changes.iter().any(|path| path.overlaps(input))) {
+                let path = Path::new(&computed.key);
+                let value = (computed.f.clone())(self);
+                self.values.insert(path.name.clone(), value);
+                self.diff.push(path);
+            }
+        }
+
+        // The diff is about to be drained
+        self.computed.iter_mut().for_each(|computed| computed.since = 0);
+    }
+
+    /// Set the theme used to resolve `$name` values in templates.
+    /// This will replace the current theme and generate a diff, causing every widget that
+    /// uses the theme to update.
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.insert(THEME_KEY, theme);
+    }
+
+    /// Save every value as text, to be restored with [`Self::restore`],
+    /// e.g. the next time the application starts.
+    /// Computed values are not saved, as they are computed from the other values,
+    /// and neither are the theme and the metrics written by the runtime.
+    ///
+    /// See [`snapshot`](crate::widgets::snapshot) for the format.
+    ///
+    /// ```
+    /// use anathema::templates::DataCtx;
+    ///
+    /// let mut ctx = DataCtx::with_value("selected-tab", 2u64);
+    /// let snapshot = ctx.snapshot();
+    ///
+    /// let mut restored = DataCtx::empty();
+    /// restored.restore(&snapshot).unwrap();
+    /// assert_eq!(restored.get_u64("selected-tab"), Some(&2));
+    /// ```
+    pub fn snapshot(&self) -> String {
+        let mut keys = self
+            .values
+            .keys()
+            .filter(|key| !is_internal(key) && !self.computed.iter().any(|computed| &computed.key == *key))
+            .collect::<Vec<_>>();
+        keys.sort();
+
+        let entries =
+            keys.into_iter().map(|key| (Value::from(key.as_str()), self.values[key].clone())).collect::<Vec<_>>();
+        snapshot::to_document(&entries)
+    }
+
+    /// Insert the values from a snapshot created with [`Self::snapshot`].
+    /// Values that are not part of the snapshot are left as they are.
+    /// This will add every key to the diff lookup.
+    ///
+    /// Nothing is inserted if the snapshot is invalid, or if a key is not a string.
+    /// The theme and the metrics are never restored, even if they are part of the snapshot.
+    pub fn restore(&mut self, snapshot: &str) -> Result<()> {
+        let entries = snapshot::from_named_document(snapshot)?;
+        for (key, value) in entries.into_iter().filter(|(key, _)| !is_internal(key)) {
+            self.insert(&key, value);
+        }
+        Ok(())
+    }
+
+    /// Keep a history of the changes, so they can be undone with [`Self::undo`].
+    /// At most `depth` steps are kept.
+    ///
+    /// Every change is a step of its own, unless it's made in a transaction.
+    /// Changes made with [`Self::set`] and [`Self::remove`] are not recorded, neither are computed values,
+    /// as they are computed again from their inputs.
+    /// The theme and the metrics (`context`) are not recorded either.
+    ///
+    /// ```
+    /// use anathema::templates::DataCtx;
+    ///
+    /// let mut ctx = DataCtx::empty();
+    /// ctx.enable_history(100);
+    /// ctx.insert("name", "Alice");
+    ///
+    /// ctx.begin_transaction();
+    /// ctx.insert("name", "Bob");
+    /// ctx.insert("age", 42u64);
+    /// ctx.end_transaction();
+    ///
+    /// ctx.undo();
+    /// assert_eq!(ctx.get_string("name").unwrap(), "Alice");
+    /// assert!(ctx.get("age").is_none());
+    ///
+    /// ctx.redo();
+    /// assert_eq!(ctx.get_u64("age"), Some(&42));
+    /// ```
+    pub fn enable_history(&mut self, depth: usize) {
+        self.history = Some(History::new(depth));
+    }
+
+    /// Group every change until [`Self::end_transaction`] into a single step.
+    /// Transactions can be nested, in which case the outermost transaction is the step.
+    pub fn begin_transaction(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            history.begin_transaction(&self.values);
+        }
+    }
+
+    /// End a transaction started with [`Self::begin_transaction`].
+    pub fn end_transaction(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            history.end_transaction(&self.values);
+        }
+    }
+
+    /// Undo the last step, ending any open transaction.
+    /// The restored paths are added to the diff lookup.
+    ///
+    /// Returns `false` if there is nothing to undo, or the history is not enabled.
+    pub fn undo(&mut self) -> bool {
+        match self.history.as_mut() {
+            Some(history) => history.undo(&mut self.values, &mut self.diff),
+            None => false,
+        }
+    }
+
+    /// Redo the last undone step.
+    /// Any change made after the undo clears the steps that can be redone.
+    /// The restored paths are added to the diff lookup.
+    ///
+    /// Returns `false` if there is nothing to redo, or the history is not enabled.
+    pub fn redo(&mut self) -> bool {
+        match self.history.as_mut() {
+            Some(history) => history.redo(&mut self.values, &mut self.diff),
+            None => false,
+        }
+    }
+
+    // Record the value before it changes.
+    // The runtime's own values are not part of the history
+    fn record(&mut self, path: &Path) {
+        if is_internal(&path.name) {
+            return;
+        }
+
+        if let Some(history) = self.history.as_mut() {
+            history.record(path, &self.values);
+        }
     }
 
     /// Drain the diffs and return a new `DataCtx` containing the differences.
+    /// The values are the root values, even if only a value inside the root changed.
     pub fn diff(&mut self) -> DataCtx {
+        self.compute();
         let mut ctx = DataCtx::empty();
-        for key in self.diff.drain(..) {
-            let value = match self.values.get(&key) {
+        for path in self.diff.drain(..) {
+            let value = match self.values.get(&path.name) {
                 Some(v) => v.clone(),
                 None => continue,
             };
-            ctx.values.insert(key, value);
+            ctx.values.insert(path.name, value);
         }
 
         ctx
     }
 
+    /// Drain the diffs and return the paths that changed.
+    pub fn take_changes(&mut self) -> Vec<Path> {
+        self.compute();
+        let mut paths: Vec<Path> = vec![];
+        for path in self.diff.drain(..) {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    /// Returns `true` if any values changed since the last call to [`Self::diff`].
+    pub fn has_changes(&self) -> bool {
+        !self.diff.is_empty()
+    }
+
     /// Is the context empty?
     pub fn is_empty(&self) -> bool {
         self.values.is_empty()
@@ -192,9 +700,10 @@ impl DataCtx {
     //     - Convenient value access -
     // -----------------------------------------------------------------------------
     pub fn get_u64_mut(&mut self, key: &str) -> Option<&mut u64> {
+        self.record(&Path::new(key));
         match self.values.get_mut(key)? {
             Value::Number(Number::Unsigned(num)) => {
-                self.diff.push(key.into());
+                self.diff.push(Path::new(key));
                 Some(num)
             }
             _ => None,
@@ -202,9 +711,10 @@ impl DataCtx {
     }
 
     pub fn get_i64_mut(&mut self, key: &str) -> Option<&mut i64> {
+        self.record(&Path::new(key));
         match self.values.get_mut(key)? {
             Value::Number(Number::Signed(num)) => {
-                self.diff.push(key.into());
+                self.diff.push(Path::new(key));
                 Some(num)
             }
             _ => None,
@@ -270,4 +780,148 @@ mod test {
         let actual = value.clone();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn lookup_by_path_in_list() {
+        let ctx = DataCtx::with_value("list", Value::List(vec![Value::from("a"), Value::from("b")]));
+        assert_eq!(ctx.by_path(&Path::from("list.1")), Some(&Value::from("b")));
+        assert_eq!(ctx.by_path(&Path::from("list.2")), None);
+    }
+
+    #[test]
+    fn set_by_path() {
+        let mut ctx = DataCtx::with_value("user", Value::Map(HashMap::new()));
+        let _ = ctx.take_changes();
+
+        assert!(ctx.set_path(&Path::from("user.settings"), Value::Map(HashMap::new())));
+        assert!(ctx.set_path(&Path::from("user.settings.theme"), "dark"));
+        assert!(!ctx.set_path(&Path::from("user.missing.theme"), "dark"));
+        assert_eq!(ctx.by_path(&Path::from("user.settings.theme")), Some(&Value::from("dark")));
+
+        *ctx.by_path_mut(&Path::from("user.settings.theme")).unwrap() = Value::from("light");
+        assert!(ctx.by_path_mut(&Path::from("user.settings.size")).is_none());
+        assert_eq!(ctx.by_path(&Path::from("user.settings.theme")), Some(&Value::from("light")));
+
+        assert_eq!(ctx.take_changes(), vec![Path::from("user.settings"), Path::from("user.settings.theme")]);
+        assert!(!ctx.has_changes());
+    }
+
+    #[test]
+    fn list_operations() {
+        let mut ctx = DataCtx::with_value("user", Value::Map(HashMap::new()));
+        let _ = ctx.take_changes();
+        let friends = Path::from("user.friends");
+
+        assert!(ctx.list_push(&friends, "a"));
+        assert!(ctx.list_push(&friends, "c"));
+        assert!(ctx.list_insert(&friends, 1, "b"));
+        assert!(!ctx.list_insert(&friends, 5, "x"));
+        assert_eq!(ctx.list_remove(&friends, 0), Some(Value::from("a")));
+        assert_eq!(ctx.list_remove(&friends, 5), None);
+        assert!(ctx.set_path(&Path::from("user.friends.0"), "B"));
+
+        let expected = Value::List(vec![Value::from("B"), Value::from("c")]);
+        assert_eq!(ctx.by_path(&friends), Some(&expected));
+        assert_eq!(ctx.take_changes(), vec![friends.clone(), Path::from("user.friends.0")]);
+
+        // Not a list
+        assert!(!ctx.list_push(&Path::from("user"), "d"));
+        assert!(!ctx.has_changes());
+
+        // Out of bounds
+        ctx.enable_history(10);
+        assert!(!ctx.list_insert(&friends, 3, "x"));
+        assert_eq!(ctx.list_remove(&friends, 2), None);
+        assert!(!ctx.has_changes());
+        assert!(!ctx.undo());
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let mut ctx = DataCtx::with_value("user", Value::Map(HashMap::from([("name".into(), "Alice".into())])));
+        ctx.insert("border", BorderStyle::Custom("╭─╮│╯─╰│".into()));
+        ctx.insert("tabs", vec![Value::Display(Display::Show), Value::Sides(Sides::TOP | Sides::BOTTOM)]);
+        ctx.insert_computed("tab-count", &["tabs"], |ctx| ctx.get_list("tabs").map(Vec::len).unwrap_or(0) as u64);
+        ctx.set_theme(&Theme::new());
+
+        let snapshot = ctx.snapshot();
+        assert!(snapshot.starts_with("\"border\" = border-style("));
+        assert!(!snapshot.contains("tab-count"));
+        assert!(!snapshot.contains(THEME_KEY));
+
+        let mut restored = DataCtx::with_value("other", true);
+        let _ = restored.take_changes();
+        restored.restore(&snapshot).unwrap();
+        assert_eq!(restored.get("user"), ctx.get("user"));
+        assert_eq!(restored.get("tabs"), ctx.get("tabs"));
+        assert_eq!(restored.get("border"), ctx.get("border"));
+        assert_eq!(restored.get_bool("other"), Some(&true));
+        assert_eq!(restored.take_changes().len(), 3);
+
+        // The runtime's own values are not restored
+        restored.restore("\"$theme\" = {}").unwrap();
+        assert!(restored.get(THEME_KEY).is_none());
+
+        // Keys have to be strings
+        assert!(restored.restore("uint(1) = true").is_err());
+    }
+
+    #[test]
+    fn user_context_key() {
+        // `context` only belongs to the runtime if it writes the metrics
+        let metrics = cfg!(feature = "metrics");
+
+        let mut ctx = DataCtx::with_value("other", 1u64);
+        ctx.enable_history(10);
+        ctx.insert("context", "a");
+        ctx.insert("context", "b");
+        assert_eq!(ctx.undo(), !metrics);
+
+        let snapshot = ctx.snapshot();
+        assert_eq!(snapshot.contains("\"context\""), !metrics);
+
+        let mut restored = DataCtx::empty();
+        restored.restore("\"context\" = \"c\"").unwrap();
+        assert_eq!(restored.get("context").is_some(), !metrics);
+    }
+
+    #[test]
+    fn computed_values() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        let mut ctx = DataCtx::with_value("price", 10u64);
+        ctx.insert("quantity", 2u64);
+        ctx.insert("name", "apple");
+        ctx.insert_computed("total", &["price", "quantity"], |ctx| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            ctx.get_u64("price").unwrap() * ctx.get_u64("quantity").unwrap()
+        });
+        ctx.insert_computed("label", &["name", "total"], |ctx| {
+            format!("{}: {}", ctx.get_string("name").unwrap(), ctx.get_u64("total").unwrap())
+        });
+        assert_eq!(ctx.get_u64("total"), Some(&20));
+        let _ = ctx.take_changes();
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+        // Unrelated change
+        ctx.insert("name", "pear");
+        assert_eq!(ctx.take_changes(), vec![Path::from("name"), Path::from("label")]);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+        // Only computed once per set of changes
+        ctx.insert("price", 5u64);
+        ctx.insert("quantity", 3u64);
+        assert_eq!(ctx.get_u64("total"), Some(&20));
+        let changes = ctx.take_changes();
+        assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+        assert!(changes.contains(&Path::from("total")));
+        assert_eq!(ctx.by_path(&Path::from("label")), Some(&Value::from("pear: 15")));
+
+        ctx.remove("total");
+        ctx.insert("price", 1u64);
+        let _ = ctx.take_changes();
+        assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+    }
 }
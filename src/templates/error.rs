@@ -1,8 +1,39 @@
 //! An error
 use std::fmt::{self, Display};
+use std::sync::Arc;
 
+use super::schema::ValueKind;
 use crate::widgets::Path;
 
+/// The location of a node in the template source.
+#[derive(Clone)]
+pub struct Span {
+    /// Line number, starting at one.
+    pub line: usize,
+    /// Column, starting at one.
+    pub col: usize,
+    src: Arc<str>,
+}
+
+impl Span {
+    pub(crate) fn new(pos: usize, src: &Arc<str>) -> Self {
+        let (line, col) = super::parser::error::src_line_no(pos, src);
+        Self { line, col: col + 1, src: src.clone() }
+    }
+
+    /// The line of source code the span is pointing to.
+    pub fn source_line(&self) -> &str {
+        self.src.lines().nth(self.line - 1).unwrap_or("")
+    }
+}
+
+// The source is omitted as it would print the entire template
+impl fmt::Debug for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Span").field("line", &self.line).field("col", &self.col).finish()
+    }
+}
+
 /// A type alias
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -21,6 +52,10 @@ pub enum Error {
     NonCollectionValue,
     /// Include path is missing.
     MissingIncludePath,
+    /// Missing name for a style declaration.
+    MissingStyleName,
+    /// A stylesheet can only contain style declarations.
+    NotAStyle(String),
     /// Missing condition for if-statment.
     MissingCondition,
     /// Missing identifier for a node that requires one.
@@ -39,8 +74,30 @@ pub enum Error {
     InvalidTextWidget,
     /// Failed to lookup id
     IdNotFound(Path),
+    /// The widget does not accept the attribute.
+    UnknownAttribute { widget: String, attribute: String },
+    /// The attribute value is not of the expected kind.
+    InvalidAttributeValue { attribute: String, expected: ValueKind },
+    /// A required attribute is missing.
+    MissingAttribute { widget: String, attribute: String },
+    /// The widget does not accept the child.
+    InvalidChild { parent: String, child: String },
+    /// The widget accepts at most one child.
+    TooManyChildren(String),
+    /// Invalid colour value.
+    InvalidColor(String),
     /// Io Error.
     Io(std::io::Error),
+    /// Failed to read a snapshot.
+    Snapshot(crate::widgets::snapshot::Error),
+    /// An error with the location in the template where it occurred.
+    Located(Box<Error>, Span),
+    /// Json error.
+    #[cfg(feature = "serde-json")]
+    Json(serde_json::Error),
+    /// Failed to convert a value with serde.
+    #[cfg(feature = "serde")]
+    Serialize(crate::widgets::serialize::Error),
 }
 
 impl Display for Error {
@@ -51,6 +108,8 @@ impl Display for Error {
             Self::WidgetConstructionFailed(e) => write!(f, "widget construction failed for {e}"),
             Self::BindingInvalidString => write!(f, "binding has to be a valid string"),
             Self::NonCollectionValue => write!(f, "the value is not a collection"),
+            Self::MissingStyleName => write!(f, "style is missing a name"),
+            Self::NotAStyle(ident) => write!(f, "a stylesheet can only contain styles, found {ident}"),
             Self::MissingCondition => write!(f, "missing condition for if-statement"),
             Self::MissingId => write!(f, "the node is missing an identifier"),
             Self::MissingIncludePath => write!(f, "include path is missing"),
@@ -58,7 +117,51 @@ impl Display for Error {
             Self::TargetIsTransition => write!(f, "the selected value is already a transition"),
             Self::InvalidTextWidget => write!(f, "invalid text widget"),
             Self::IdNotFound(path) => write!(f, "node id was not found in the context: {path}"),
+            Self::UnknownAttribute { widget, attribute } => write!(f, "{widget} has no attribute \"{attribute}\""),
+            Self::InvalidAttributeValue { attribute, expected } => {
+                write!(f, "invalid value for \"{attribute}\", expected {expected}")
+            }
+            Self::MissingAttribute { widget, attribute } => {
+                write!(f, "{widget} is missing the required attribute \"{attribute}\"")
+            }
+            Self::InvalidChild { parent, child } => write!(f, "{parent} can not have {child} as a child"),
+            Self::TooManyChildren(parent) => write!(f, "{parent} can only have one child"),
+            Self::InvalidColor(color) => write!(f, "invalid colour: {color}"),
             Self::Io(e) => write!(f, "{e}"),
+            Self::Snapshot(e) => write!(f, "{e}"),
+            Self::Located(e, span) => {
+                let line_no = span.line.to_string();
+                let gutter = " ".repeat(line_no.len());
+                writeln!(f, "error: {e}")?;
+                writeln!(f, "{gutter}--> line {}, column {}", span.line, span.col)?;
+                writeln!(f, "{gutter} |")?;
+                writeln!(f, "{line_no} | {}", span.source_line())?;
+                write!(f, "{gutter} | {:>col$}", "^", col = span.col)
+            }
+            #[cfg(feature = "serde-json")]
+            Self::Json(e) => write!(f, "{e}"),
+            #[cfg(feature = "serde")]
+            Self::Serialize(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error {
+    /// Attach a location to the error.
+    /// An error that already has a location keeps it, as the innermost location
+    /// is the most precise one.
+    pub(crate) fn at(self, span: &Span) -> Self {
+        match self {
+            Self::Located(..) => self,
+            e => Self::Located(Box::new(e), span.clone()),
+        }
+    }
+
+    /// The location of the error, if known.
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            Self::Located(_, span) => Some(span),
+            _ => None,
         }
     }
 }
@@ -74,3 +177,23 @@ impl From<std::io::Error> for Error {
         Self::Io(e)
     }
 }
+
+impl From<crate::widgets::snapshot::Error> for Error {
+    fn from(e: crate::widgets::snapshot::Error) -> Self {
+        Self::Snapshot(e)
+    }
+}
+
+#[cfg(feature = "serde-json")]
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<crate::widgets::serialize::Error> for Error {
+    fn from(e: crate::widgets::serialize::Error) -> Self {
+        Self::Serialize(e)
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::ctx::{lookup, lookup_mut, split_last};
+use crate::widgets::{Path, Value};
+
+// The value at a path before and after a change
+#[derive(Debug, Clone, PartialEq)]
+struct Change {
+    path: Path,
+    before: Option<Value>,
+    after: Option<Value>,
+}
+
+/// Undo / redo history of a [`DataCtx`](super::DataCtx).
+///
+/// The value before a change is recorded when the change is made,
+/// and the value after the change once the step is committed:
+/// when the next change is made, unless there is an open transaction.
+/// This way a mutable reference returned from the context is part of the history as well.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct History {
+    undo: VecDeque<Vec<Change>>,
+    redo: Vec<Vec<Change>>,
+    pending: Vec<Change>,
+    transactions: usize,
+    depth: usize,
+}
+
+impl History {
+    pub(super) fn new(depth: usize) -> Self {
+        Self { undo: VecDeque::new(), redo: vec![], pending: vec![], transactions: 0, depth }
+    }
+
+    /// Record the value at the path before it changes.
+    pub(super) fn record(&mut self, path: &Path, values: &HashMap<String, Value>) {
+        if self.transactions == 0 {
+            self.commit(values);
+        }
+
+        // Only the first value before the change is of interest
+        if self.pending.iter().any(|change| change.path == *path) {
+            return;
+        }
+
+        let before = lookup(values, path).cloned();
+        self.pending.push(Change { path: path.clone(), before, after: None });
+    }
+
+    pub(super) fn begin_transaction(&mut self, values: &HashMap<String, Value>) {
+        if self.transactions == 0 {
+            self.commit(values);
+        }
+        self.transactions += 1;
+    }
+
+    pub(super) fn end_transaction(&mut self, values: &HashMap<String, Value>) {
+        self.transactions = self.transactions.saturating_sub(1);
+        if self.transactions == 0 {
+            self.commit(values);
+        }
+    }
+
+    fn commit(&mut self, values: &HashMap<String, Value>) {
+        let mut changes = std::mem::take(&mut self.pending);
+        changes.iter_mut().for_each(|change| change.after = lookup(values, &change.path).cloned());
+        // Paths can overlap, so a single change can't be dropped
+        // even if the value is the same before and after
+        if changes.iter().all(|change| change.before == change.after) {
+            return;
+        }
+
+        self.redo.clear();
+        self.push_undo(changes);
+    }
+
+    fn push_undo(&mut self, changes: Vec<Change>) {
+        self.undo.push_back(changes);
+        if self.undo.len() > self.depth {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Restore the values of the last step, closing any open transaction.
+    pub(super) fn undo(&mut self, values: &mut HashMap<String, Value>, diff: &mut Vec<Path>) -> bool {
+        self.transactions = 0;
+        self.commit(values);
+
+        let changes = match self.undo.pop_back() {
+            Some(changes) => changes,
+            None => return false,
+        };
+
+        // In reverse, so the value recorded first is the one that remains
+        for change in changes.iter().rev() {
+            restore(values, &change.path, change.before.clone());
+            diff.push(change.path.clone());
+        }
+
+        self.redo.push(changes);
+        true
+    }
+
+    /// Apply the last undone step again.
+    pub(super) fn redo(&mut self, values: &mut HashMap<String, Value>, diff: &mut Vec<Path>) -> bool {
+        self.transactions = 0;
+        self.commit(values);
+
+        let changes = match self.redo.pop() {
+            Some(changes) => changes,
+            None => return false,
+        };
+
+        for change in &changes {
+            restore(values, &change.path, change.after.clone());
+            diff.push(change.path.clone());
+        }
+
+        self.push_undo(changes);
+        true
+    }
+}
+
+// Set the value at the path, or remove it if the value is `None`
+fn restore(values: &mut HashMap<String, Value>, path: &Path, value: Option<Value>) {
+    let (parent, name) = match split_last(path) {
+        Some(split) => split,
+        None => {
+            match value {
+                Some(value) => values.insert(path.name.clone(), value),
+                None => values.remove(&path.name),
+            };
+            return;
+        }
+    };
+
+    match (lookup_mut(values, &parent), value) {
+        (Some(Value::Map(map)), Some(value)) => {
+            map.insert(name, value);
+        }
+        (Some(Value::Map(map)), None) => {
+            map.remove(&name);
+        }
+        (Some(Value::List(list)), Some(value)) => {
+            if let Some(old) = name.parse::<usize>().ok().and_then(|index| list.get_mut(index)) {
+                *old = value;
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::templates::DataCtx;
+    use crate::widgets::{Path, Value};
+
+    fn ctx() -> DataCtx {
+        let mut ctx = DataCtx::empty();
+        ctx.enable_history(10);
+        ctx
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut ctx = ctx();
+        ctx.insert("counter", 1u64);
+        *ctx.get_u64_mut("counter").unwrap() += 1;
+        ctx.insert("counter", 10u64);
+        let _ = ctx.take_changes();
+
+        assert!(ctx.undo());
+        assert_eq!(ctx.get_u64("counter"), Some(&2));
+        assert_eq!(ctx.take_changes(), vec![Path::from("counter")]);
+
+        assert!(ctx.undo());
+        assert_eq!(ctx.get_u64("counter"), Some(&1));
+        assert!(ctx.undo());
+        assert!(ctx.get("counter").is_none());
+        assert!(!ctx.undo());
+
+        assert!(ctx.redo());
+        assert!(ctx.redo());
+        assert_eq!(ctx.get_u64("counter"), Some(&2));
+
+        // A new change clears the redo steps
+        ctx.insert("counter", 5u64);
+        assert!(!ctx.redo());
+        assert!(ctx.undo());
+        assert_eq!(ctx.get_u64("counter"), Some(&2));
+    }
+
+    #[test]
+    fn transactions() {
+        let mut ctx = ctx();
+        ctx.insert("user", Value::Map(Default::default()));
+
+        ctx.begin_transaction();
+        ctx.set_path(&Path::from("user.name"), "Alice");
+        ctx.begin_transaction();
+        ctx.list_push(&Path::from("user.friends"), "Bob");
+        ctx.end_transaction();
+        ctx.list_push(&Path::from("user.friends"), "Carol");
+        ctx.insert("user", Value::Map(Default::default()));
+        ctx.set_path(&Path::from("user.name"), "Dave");
+        ctx.end_transaction();
+
+        assert!(ctx.undo());
+        assert_eq!(ctx.get("user"), Some(&Value::Map(Default::default())));
+        assert!(ctx.redo());
+        assert_eq!(ctx.by_path(&Path::from("user.name")), Some(&Value::from("Dave")));
+        assert!(ctx.by_path(&Path::from("user.friends")).is_none());
+
+        assert!(ctx.undo());
+        assert!(ctx.undo());
+        assert!(ctx.get("user").is_none());
+    }
+
+    #[test]
+    fn computed_values_are_not_recorded() {
+        let mut ctx = ctx();
+        ctx.insert("items", vec![Value::from(1u64)]);
+        ctx.insert_computed("count", &["items"], |ctx| ctx.get_list("items").map(Vec::len).unwrap_or(0) as u64);
+        ctx.list_push(&Path::from("items"), 2u64);
+        let _ = ctx.take_changes();
+        assert_eq!(ctx.get_u64("count"), Some(&2));
+
+        assert!(ctx.undo());
+        let _ = ctx.take_changes();
+        assert_eq!(ctx.get_u64("count"), Some(&1));
+
+        assert!(ctx.undo());
+        assert!(!ctx.undo());
+        let _ = ctx.take_changes();
+        assert!(ctx.get("items").is_none());
+        assert_eq!(ctx.get_u64("count"), Some(&0));
+    }
+
+    #[test]
+    fn depth_limit() {
+        let mut ctx = DataCtx::empty();
+        ctx.enable_history(2);
+        for i in 0..5u64 {
+            ctx.insert("value", i);
+        }
+        // Changes that don't change anything are not recorded
+        ctx.insert("value", 4u64);
+        ctx.set_path(&Path::from("missing.value"), 1u64);
+
+        assert!(ctx.undo());
+        assert!(ctx.undo());
+        assert!(!ctx.undo());
+        assert_eq!(ctx.get_u64("value"), Some(&2));
+    }
+
+    #[test]
+    fn no_history() {
+        let mut ctx = DataCtx::with_value("value", 1u64);
+        ctx.insert("value", 2u64);
+        assert!(!ctx.undo());
+        assert!(!ctx.redo());
+    }
+}
@@ -4,17 +4,19 @@ use crate::widgets::{HorzEdge, VertEdge};
 
 use super::error::{Error, Result};
 use super::nodes::{Kind, Node};
+use super::schema::{Children, Schema, ValueKind};
 
 use crate::widgets::{
     fields, Align, Alignment, Animation, Border, Canvas, Expand, HStack, Position, Spacer, Text, TextSpan, VStack,
     Value, Widget, WidgetContainer, ZStack,
 };
 
-const RESERVED_NAMES: &[&str] = &["if", "for", "else"];
+const RESERVED_NAMES: &[&str] = &["if", "for", "else", "with", "style"];
 
 /// `WidgetLookup` contains functions for producing widgets based on the `Node`s ident.
 pub struct WidgetLookup {
     inner: HashMap<&'static str, &'static Factory>,
+    schemas: HashMap<&'static str, Schema>,
 }
 
 type Factory = dyn Fn(&Node, &WidgetLookup) -> Result<WidgetContainer> + Send + Sync + 'static;
@@ -32,14 +34,33 @@ impl WidgetLookup {
         self.inner.insert(ident, factory);
     }
 
+    /// Register a widget together with the [`Schema`] used to validate its attributes and children.
+    pub fn register_with_schema(&mut self, ident: &'static str, factory: &'static Factory, schema: Schema) {
+        self.register(ident, factory);
+        self.schemas.insert(ident, schema);
+    }
+
+    /// The schema of a registered widget.
+    /// Widgets registered without a schema are not validated.
+    pub fn schema(&self, ident: &str) -> Option<&Schema> {
+        self.schemas.get(ident)
+    }
+
+    pub(crate) fn contains(&self, ident: &str) -> bool {
+        self.inner.contains_key(ident)
+    }
+
     pub(crate) fn make(&self, node: &Node) -> Result<WidgetContainer> {
         let ident = node.ident();
         let f = self.inner.get(ident).ok_or_else(|| Error::UnregisteredWidget(ident.to_string()))?;
 
         let mut widget = f(node, self)?;
 
-        node.attributes.padding_all().map(|padding| widget.padding = padding);
+        if let Some(padding) = node.attributes.padding_all() {
+            widget.padding = padding;
+        }
         widget.background = node.attributes.background();
+        widget.opacity = node.attributes.opacity();
 
         let transitions = node.attributes.transitions();
 
@@ -49,6 +70,12 @@ impl WidgetLookup {
             widget.animation.push(k, animation);
         }
 
+        for (k, value, duration, easing) in node.attributes.color_transitions() {
+            let mut animation = Animation::new(duration, easing);
+            animation.set_src(value);
+            widget.animation.push_color(k, animation);
+        }
+
         Ok(widget)
     }
 }
@@ -77,6 +104,7 @@ pub fn text_widget(node: &Node, _: &WidgetLookup) -> Result<WidgetContainer> {
 
         let mut span = TextSpan::new(text);
         span.style = attribs.style();
+        span.link = attribs.link();
         widget.add_span(span);
     }
 
@@ -85,18 +113,70 @@ pub fn text_widget(node: &Node, _: &WidgetLookup) -> Result<WidgetContainer> {
 
 impl Default for WidgetLookup {
     fn default() -> Self {
-        let mut inst = Self { inner: HashMap::new() };
-
-        inst.register("alignment", &alignment_widget);
-        inst.register("border", &border_widget);
-        inst.register("canvas", &canvas_widget);
-        inst.register("expand", &expand_widget);
-        inst.register("position", &position_widget);
-        inst.register("spacer", &spacer_widget);
-        inst.register("text", &text_widget);
-        inst.register("vstack", &vstack_widget);
-        inst.register("hstack", &hstack_widget);
-        inst.register("zstack", &zstack_widget);
+        let mut inst = Self { inner: HashMap::new(), schemas: HashMap::new() };
+
+        let sized = |children| {
+            Schema::new(children)
+                .optional(fields::WIDTH, ValueKind::Number)
+                .optional(fields::HEIGHT, ValueKind::Number)
+                .optional(fields::MIN_WIDTH, ValueKind::Number)
+                .optional(fields::MIN_HEIGHT, ValueKind::Number)
+        };
+
+        inst.register_with_schema(
+            "alignment",
+            &alignment_widget,
+            Schema::new(Children::One).optional(fields::ALIGNMENT, ValueKind::Alignment),
+        );
+        inst.register_with_schema(
+            "border",
+            &border_widget,
+            sized(Children::One)
+                .optional(fields::BORDER_STYLE, ValueKind::BorderStyle)
+                .optional(fields::SIDES, ValueKind::Sides)
+                .with_style(),
+        );
+        inst.register_with_schema(
+            "canvas",
+            &canvas_widget,
+            Schema::new(Children::None)
+                .optional(fields::WIDTH, ValueKind::Number)
+                .optional(fields::HEIGHT, ValueKind::Number),
+        );
+        inst.register_with_schema(
+            "expand",
+            &expand_widget,
+            Schema::new(Children::One)
+                .optional(fields::DIRECTION, ValueKind::Direction)
+                .optional(fields::FACTOR, ValueKind::Number)
+                .optional(fields::FILL, ValueKind::String)
+                .with_style(),
+        );
+        inst.register_with_schema(
+            "position",
+            &position_widget,
+            Schema::new(Children::One)
+                .optional(fields::LEFT, ValueKind::Number)
+                .optional(fields::RIGHT, ValueKind::Number)
+                .optional(fields::TOP, ValueKind::Number)
+                .optional(fields::BOTTOM, ValueKind::Number),
+        );
+        inst.register_with_schema("spacer", &spacer_widget, Schema::new(Children::None));
+        inst.register_with_schema(
+            "text",
+            &text_widget,
+            Schema::new(Children::Spans)
+                .optional(fields::TRIM_START, ValueKind::Bool)
+                .optional(fields::TRIM_END, ValueKind::Bool)
+                .optional(fields::COLLAPSE_SPACES, ValueKind::Bool)
+                .optional(fields::WRAP, ValueKind::Wrap)
+                .optional(fields::TEXT_ALIGN, ValueKind::TextAlignment)
+                .optional(fields::LINK, ValueKind::String)
+                .with_style(),
+        );
+        inst.register_with_schema("vstack", &vstack_widget, sized(Children::Many));
+        inst.register_with_schema("hstack", &hstack_widget, sized(Children::Many));
+        inst.register_with_schema("zstack", &zstack_widget, sized(Children::Many));
 
         inst
     }
@@ -265,7 +345,10 @@ fn expand_widget(node: &Node, lookup: &WidgetLookup) -> Result<WidgetContainer>
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::widgets::{fields, Attributes, BorderStyle, NodeId};
+    use std::time::Duration;
+
+    use crate::display::Color;
+    use crate::widgets::{fields, Attributes, BorderStyle, Easing, NodeId, Value};
 
     fn node_to_widget(node: &Node) -> WidgetContainer {
         let lookup = WidgetLookup::default();
@@ -278,8 +361,13 @@ mod test {
         attributes.set(fields::MIN_WIDTH, 10u64);
         attributes.set(fields::MIN_HEIGHT, 3u64);
         attributes.set(fields::BORDER_STYLE, BorderStyle::Custom("01234567".into()));
-        let node =
-            Node { kind: Kind::Node { ident: "border".into() }, children: vec![], id: NodeId::auto(), attributes };
+        let node = Node {
+            kind: Kind::Node { ident: "border".into() },
+            children: vec![],
+            id: NodeId::auto(),
+            attributes,
+            origin: 0,
+        };
 
         let mut widget = node_to_widget(&node);
         let border = widget.to::<Border>();
@@ -288,13 +376,68 @@ mod test {
         assert_eq!(['0', '1', '2', '3', '4', '5', '6', '7'], border.edges);
     }
 
+    #[test]
+    fn lookup_text_link() {
+        let mut attributes = Attributes::empty();
+        attributes.set(fields::LINK, "https://example.com");
+        let span =
+            Node { kind: Kind::Span("here".into()), children: vec![], id: NodeId::auto(), attributes, origin: 0 };
+        let node = Node {
+            kind: Kind::Node { ident: "text".into() },
+            children: vec![span],
+            id: NodeId::auto(),
+            attributes: Attributes::empty(),
+            origin: 0,
+        };
+
+        let mut widget = node_to_widget(&node);
+        let text = widget.to::<Text>();
+        assert_eq!(text.spans[0].link.as_deref(), Some("https://example.com"));
+    }
+
+    #[test]
+    fn lookup_background_transition() {
+        let red = Color::Rgb { r: 255, g: 0, b: 0 };
+        let blue = Color::Rgb { r: 0, g: 0, b: 255 };
+        let transition =
+            |color| Value::Transition(Box::new(Value::Color(color)), Duration::from_millis(100), Easing::Linear);
+
+        let mut attributes = Attributes::empty();
+        attributes.set(fields::BACKGROUND, transition(red));
+        attributes.set(fields::OPACITY, 50u64);
+        let node = Node {
+            kind: Kind::Node { ident: "vstack".into() },
+            children: vec![],
+            id: NodeId::auto(),
+            attributes,
+            origin: 0,
+        };
+
+        let mut widget = node_to_widget(&node);
+        assert_eq!(widget.background, Some(red));
+        assert_eq!(widget.opacity, Some(50));
+
+        // The background fades from red to blue
+        widget.update(Attributes::new(fields::BACKGROUND, transition(blue)));
+        assert_eq!(widget.background, Some(red));
+        widget.animate(Duration::from_millis(50));
+        assert_eq!(widget.background, Some(Color::Rgb { r: 128, g: 0, b: 128 }));
+        widget.animate(Duration::from_millis(50));
+        assert_eq!(widget.background, Some(blue));
+    }
+
     #[test]
     fn lookup_vstack() {
         let mut attributes = Attributes::empty();
         attributes.set(fields::MIN_WIDTH, 10u64);
         attributes.set(fields::MIN_HEIGHT, 3u64);
-        let node =
-            Node { kind: Kind::Node { ident: "vstack".into() }, children: vec![], id: NodeId::auto(), attributes };
+        let node = Node {
+            kind: Kind::Node { ident: "vstack".into() },
+            children: vec![],
+            id: NodeId::auto(),
+            attributes,
+            origin: 0,
+        };
 
         let mut widget = node_to_widget(&node);
         let stack = widget.to::<VStack>();
@@ -307,8 +450,13 @@ mod test {
         let mut attributes = Attributes::empty();
         attributes.set(fields::MIN_WIDTH, 10u64);
         attributes.set(fields::MIN_HEIGHT, 3u64);
-        let node =
-            Node { kind: Kind::Node { ident: "hstack".into() }, children: vec![], id: NodeId::auto(), attributes };
+        let node = Node {
+            kind: Kind::Node { ident: "hstack".into() },
+            children: vec![],
+            id: NodeId::auto(),
+            attributes,
+            origin: 0,
+        };
 
         let mut widget = node_to_widget(&node);
         let stack = widget.to::<HStack>();
@@ -321,8 +469,13 @@ mod test {
         let mut attributes = Attributes::empty();
         attributes.set(fields::MIN_WIDTH, 10u64);
         attributes.set(fields::MIN_HEIGHT, 3u64);
-        let node =
-            Node { kind: Kind::Node { ident: "zstack".into() }, children: vec![], id: NodeId::auto(), attributes };
+        let node = Node {
+            kind: Kind::Node { ident: "zstack".into() },
+            children: vec![],
+            id: NodeId::auto(),
+            attributes,
+            origin: 0,
+        };
 
         let mut widget = node_to_widget(&node);
         let stack = widget.to::<ZStack>();
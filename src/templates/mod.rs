@@ -1,16 +1,31 @@
+use std::sync::Arc;
+
+use crate::widgets::Path;
+
 pub mod error;
 
 pub use error::Result;
 
 mod ctx;
+mod history;
 mod lookup;
 mod nodes;
 mod parser;
+mod schema;
+mod theme;
+mod view_model;
 
-pub use ctx::{DataCtx, IncludeCache, NodeCtx, SubContext};
+pub use ctx::{DataCtx, IncludeCache, NodeCtx, Stylesheet, SubContext};
 pub use lookup::WidgetLookup;
 pub use nodes::widget::WidgetNode;
 pub use nodes::{diff, Node};
+pub use schema::{AttributeSchema, Children, Schema, ValueKind};
+pub use theme::Theme;
+pub use view_model::ViewModel;
+
+/// Derive [`ViewModel`](trait@ViewModel). Requires the `derive` feature.
+#[cfg(feature = "derive")]
+pub use anathema_derive::ViewModel;
 
 // Src -> WidgetNodes -> Nodes -> Widgets
 // WidgetNodes -> Nodes -> Diff -> Widgets
@@ -19,7 +34,20 @@ pub fn parse(src: &str) -> Result<Vec<WidgetNode>> {
     let lexer = parser::lexer::Lexer::new(src);
     let parser = parser::Parser::new(lexer);
     let node_tree = nodes::template::create_tree(parser)?;
-    nodes::widget::to_widget_nodes(node_tree, false)
+    nodes::widget::to_widget_nodes(node_tree, false, &Arc::from(src), &mut 0)
+}
+
+/// Validate the widget nodes against the schemas registered in the lookup.
+/// Every problem found is returned rather than stopping at the first one.
+///
+/// Attributes bound to data are only type checked once the template is evaluated.
+pub fn validate(widget_nodes: &[WidgetNode], lookup: &WidgetLookup) -> std::result::Result<(), Vec<error::Error>> {
+    let mut errors = vec![];
+    nodes::validate::validate(widget_nodes, lookup, None, &mut errors);
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors),
+    }
 }
 
 pub fn to_nodes(
@@ -34,6 +62,24 @@ pub fn to_nodes(
     Ok(nodes)
 }
 
+/// Update the nodes created from the widget nodes, where `changed` contains the paths
+/// that changed since `old_nodes` were created (see [`DataCtx::take_changes`]).
+///
+/// Only the widget nodes that depend on a changed value are evaluated again,
+/// the old nodes are reused for everything else.
+pub fn update_nodes(
+    widget_nodes: &[WidgetNode],
+    data_ctx: &SubContext<'_>,
+    node_ctx: &mut NodeCtx<'_>,
+    old_nodes: &[Node],
+    changed: &[Path],
+) -> Result<Vec<Node>> {
+    if nodes::style_changed(widget_nodes, &node_ctx.stylesheet, changed) {
+        return to_nodes(widget_nodes, data_ctx, node_ctx);
+    }
+    nodes::update_nodes(widget_nodes, data_ctx, node_ctx, old_nodes, changed)
+}
+
 pub fn build_widget_tree(
     lookup: &WidgetLookup,
     widget_nodes: &[WidgetNode],
@@ -89,4 +135,89 @@ mod test {
             "#,
         );
     }
+
+    #[test]
+    fn validate_template() {
+        let lookup = WidgetLookup::default();
+
+        let input = r#"
+        border [sides: top, foreground: red]:
+            vstack [width: {{ width }}]:
+                text [bold: true, wrap: word]: "hello"
+                    span [italic: true]: "world"
+        "#;
+        assert!(validate(&parse(input).unwrap(), &lookup).is_ok());
+
+        let input = r#"
+        vstack [width: "wide", flavour: "tea"]:
+            text: "hello"
+                vstack:
+                    spacer:
+            fancy:
+            span: "lonely"
+        "#;
+        let errors = validate(&parse(input).unwrap(), &lookup).unwrap_err();
+        let errors = errors.iter().map(|e| (e.span().unwrap().line, e.to_string())).collect::<Vec<_>>();
+        let messages = errors.iter().map(|(line, e)| (*line, e.lines().next().unwrap())).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (2, "error: vstack has no attribute \"flavour\""),
+                (2, "error: invalid value for \"width\", expected number"),
+                (4, "error: text can not have vstack as a child"),
+                (6, "error: unregistered widget: fancy"),
+                (7, "error: vstack can not have span as a child"),
+            ]
+        );
+
+        let input = "alignment:\n    text: \"a\"\n    text: \"b\"";
+        let errors = validate(&parse(input).unwrap(), &lookup).unwrap_err();
+        assert!(
+            matches!(&errors[..], [error::Error::Located(e, _)] if matches!(**e, error::Error::TooManyChildren(_)))
+        );
+
+        // Control flow producing a single node is accepted
+        let input = r#"
+        alignment:
+            if [cond: {{ a }}]:
+                text [id: "a"]: "a"
+            else:
+                text [id: "b"]: "b"
+        border:
+            for [data: {{ items }}, binding: item]:
+                text [id: {{ item }}]: "{{ item }}"
+        "#;
+        assert!(validate(&parse(input).unwrap(), &lookup).is_ok());
+
+        let input =
+            "border:\n    if [cond: {{ a }}]:\n        text [id: \"a\"]: \"a\"\n        text [id: \"b\"]: \"b\"";
+        let errors = validate(&parse(input).unwrap(), &lookup).unwrap_err();
+        assert!(
+            matches!(&errors[..], [error::Error::Located(e, _)] if matches!(**e, error::Error::TooManyChildren(_)))
+        );
+
+        // The error points at the second child, not counting style declarations
+        let input = "border:\n    style [bold: true]: \"x\"\n    text: \"a\"\n    text: \"b\"";
+        let errors = validate(&parse(input).unwrap(), &lookup).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].span().unwrap().line, 4);
+    }
+
+    #[test]
+    fn error_location() {
+        let input = "vstack:\n    for [data: {{ items }}, binding: item]:\n        text: \"missing id\"";
+        let err = parse(input).unwrap_err();
+        assert!(matches!(err, error::Error::Located(ref e, _) if matches!(**e, error::Error::MissingId)));
+
+        let span = err.span().unwrap();
+        assert_eq!((span.line, span.col), (3, 9));
+
+        let expected = "\
+error: the node is missing an identifier
+ --> line 3, column 9
+  |
+3 |         text: \"missing id\"
+  |         ^";
+        assert_eq!(err.to_string(), expected);
+    }
 }
@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use crate::templates::parser::Text;
+use crate::widgets::{Attributes, Fragment, Path, Value};
+
+/// The paths in the [`DataCtx`](crate::templates::DataCtx) that a widget node,
+/// and all of its children, depend on.
+///
+/// `{{ user.name }}` depends on `user.name`, so it's affected by a change to `user`
+/// or `user.name`, but not `user.age`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Dependencies {
+    paths: HashSet<Path>,
+    // Paths used by style statements.
+    // A style applies to every node evaluated after it, not just the children.
+    style_paths: HashSet<Path>,
+    // Includes have to be evaluated on every rebuild,
+    // as the content of an include is not known up front
+    always: bool,
+    // The node, or one of its children, declares a style.
+    // The stylesheet is built up from scratch on every rebuild,
+    // so the style has to be declared again even if nothing changed
+    declares_style: bool,
+}
+
+impl Dependencies {
+    pub(crate) fn always() -> Self {
+        Self { always: true, ..Default::default() }
+    }
+
+    pub(crate) fn value(&mut self, value: &Value) {
+        match value {
+            Value::DataBinding(path) => {
+                self.paths.insert(path.clone());
+            }
+            Value::Fragments(fragments) => self.fragments(fragments),
+            Value::Transition(value, ..) => self.value(value),
+            Value::List(values) => values.iter().for_each(|value| self.value(value)),
+            Value::Map(values) => values.values().for_each(|value| self.value(value)),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn attributes(&mut self, attributes: &Attributes) {
+        attributes.into_iter().for_each(|(_, value)| self.value(value));
+    }
+
+    pub(crate) fn text(&mut self, text: &Text) {
+        if let Text::Fragments(fragments) = text {
+            self.fragments(fragments);
+        }
+    }
+
+    fn fragments(&mut self, fragments: &[Fragment]) {
+        for fragment in fragments {
+            if let Fragment::Data(path) = fragment {
+                self.paths.insert(path.clone());
+            }
+        }
+    }
+
+    /// Mark the paths added so far as used by a style.
+    pub(crate) fn style(&mut self) {
+        self.style_paths.extend(self.paths.iter().cloned());
+        self.declares_style = true;
+    }
+
+    pub(crate) fn merge(&mut self, other: &Dependencies) {
+        self.paths.extend(other.paths.iter().cloned());
+        self.style_paths.extend(other.style_paths.iter().cloned());
+        self.always |= other.always;
+        self.declares_style |= other.declares_style;
+    }
+
+    /// Returns `true` if the node, or one of its children, declares a style.
+    pub(crate) fn declares_style(&self) -> bool {
+        self.declares_style
+    }
+
+    /// Returns `true` if the node has to be evaluated again.
+    pub(crate) fn changed(&self, changes: &[Path]) -> bool {
+        self.always || overlaps(&self.paths, changes)
+    }
+
+    /// Returns `true` if a style depends on any of the changed paths.
+    pub(crate) fn style_changed(&self, changes: &[Path]) -> bool {
+        overlaps(&self.style_paths, changes)
+    }
+}
+
+fn overlaps(paths: &HashSet<Path>, changes: &[Path]) -> bool {
+    changes.iter().any(|change| paths.iter().any(|path| path.overlaps(change)))
+}
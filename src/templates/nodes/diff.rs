@@ -2,7 +2,7 @@ use std::iter::zip;
 
 use crate::display::Style;
 use crate::templates::WidgetLookup;
-use crate::widgets::{Attributes, NodeId, WidgetContainer};
+use crate::widgets::{fields, Attributes, NodeId, WidgetContainer};
 
 use super::{Kind, Node};
 
@@ -46,6 +46,7 @@ pub(crate) struct SpanDiff {
     index: usize,
     text: Option<String>,
     style: Option<Style>,
+    link: Option<Option<String>>,
 }
 
 impl SpanDiff {
@@ -54,11 +55,13 @@ impl SpanDiff {
 
         let style = if !attribs.is_empty() { Some(attribs.style()) } else { None };
 
-        if text.is_none() && style.is_none() {
+        let link = attribs.has(fields::LINK).then(|| attribs.link());
+
+        if text.is_none() && style.is_none() && link.is_none() {
             return None;
         }
 
-        let inst = Self { index, text, style };
+        let inst = Self { index, text, style, link };
 
         Some(inst)
     }
@@ -118,7 +121,7 @@ impl Changes {
                 if !change.span_diff.is_empty() {
                     let text_widget = node.to::<crate::widgets::Text>();
                     for diff in change.span_diff {
-                        text_widget.update_span(diff.index, diff.text, diff.style);
+                        text_widget.update_span(diff.index, diff.text, diff.style, diff.link);
                     }
                 }
             }
@@ -1,11 +1,14 @@
-use crate::widgets::{Attributes, Fragment, NodeId, Value};
+use crate::widgets::{Attributes, Fragment, NodeId, Path, Value};
 
-use super::ctx::{NodeCtx, SubContext};
+use super::ctx::{NodeCtx, Stylesheet, SubContext};
 use super::error::{Error, Result};
+use deps::Dependencies;
 use widget::{Statement, WidgetNode};
 
+mod deps;
 pub mod diff;
 pub mod template;
+pub(crate) mod validate;
 pub mod widget;
 
 static DEFAULT_VALUE: &Value = &Value::String(String::new());
@@ -14,7 +17,7 @@ const MAX_INCLUDE_DEPTH: usize = 42;
 // -----------------------------------------------------------------------------
 //     - Node kind -
 // -----------------------------------------------------------------------------
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Kind {
     Node { ident: String },
     Span(String),
@@ -23,12 +26,14 @@ pub enum Kind {
 // -----------------------------------------------------------------------------
 //     - Node -
 // -----------------------------------------------------------------------------
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Node {
     pub kind: Kind,
     pub children: Vec<Node>,
     pub attributes: Attributes,
     pub(crate) id: NodeId,
+    // The index of the widget node that produced this node
+    pub(crate) origin: usize,
 }
 
 impl Node {
@@ -162,12 +167,54 @@ fn if_statement(
     Ok(vec![])
 }
 
+fn with_statement(
+    data_ctx: &SubContext<'_>,
+    node_ctx: &mut NodeCtx<'_>,
+    bindings: &[(String, Value)],
+    children: &[WidgetNode],
+) -> Result<Vec<Node>> {
+    // All bindings are resolved against the outer context,
+    // so one binding can not refer to another binding in the same `with`.
+    let mut sub_ctx = data_ctx.sub_empty();
+    for (name, value) in bindings {
+        let value = match value {
+            Value::DataBinding(path) => data_ctx.by_path(path).unwrap_or(DEFAULT_VALUE).clone(),
+            Value::Fragments(fragments) => Value::String(fragments_to_string(fragments, data_ctx)),
+            value => value.clone(),
+        };
+        sub_ctx.insert(name, value);
+    }
+
+    let mut nodes = vec![];
+    for child in children {
+        nodes.extend(to_nodes(child, &sub_ctx, node_ctx)?);
+    }
+
+    Ok(nodes)
+}
+
 fn widget_node_to_nodes(
     widget_node: &WidgetNode,
     children: &[WidgetNode],
     data_ctx: &SubContext<'_>,
     node_ctx: &mut NodeCtx<'_>,
 ) -> Result<Vec<Node>> {
+    let mut nodes = vec![];
+    // let children = children.iter().flat_map(|n| to_nodes(n, data_ctx, node_ctx)).collect();
+    for child in children {
+        nodes.extend(to_nodes(child, data_ctx, node_ctx)?);
+    }
+
+    Ok(vec![to_node(widget_node, nodes, data_ctx, node_ctx)?])
+}
+
+// Create a node from a widget node, where the children are already evaluated.
+fn to_node(
+    widget_node: &WidgetNode,
+    children: Vec<Node>,
+    data_ctx: &SubContext<'_>,
+    node_ctx: &mut NodeCtx<'_>,
+) -> Result<Node> {
     // Node kind
     let kind = match widget_node.ident.as_str() {
         "span" => {
@@ -180,24 +227,110 @@ fn widget_node_to_nodes(
         ident => Kind::Node { ident: ident.to_string() },
     };
 
-    let mut nodes = vec![];
-    // let children = children.iter().flat_map(|n| to_nodes(n, data_ctx, node_ctx)).collect();
-    for child in children {
-        nodes.extend(to_nodes(child, data_ctx, node_ctx)?);
-    }
-    // let children = children.iter().flat_map(|n| to_nodes(n, data_ctx, node_ctx)).collect();
-    let attributes = lookup_attributes(&widget_node.attributes, data_ctx);
+    // Apply the classes before the lookup, so values bound to data in a stylesheet are resolved
+    let mut attributes = widget_node.attributes.clone();
+    node_ctx.stylesheet.apply(&mut attributes);
+    let attributes = lookup_attributes(&attributes, data_ctx);
 
     let id = match widget_node.node_id() {
         NodeId::Value(Value::DataBinding(path)) => match data_ctx.by_path(&path) {
             Some(data) => NodeId::Value(data.clone()),
-            None => return Err(Error::IdNotFound(path)),
+            None => return Err(Error::IdNotFound(path).at(&widget_node.span)),
         },
         NodeId::Value(Value::Fragments(ref fragments)) => NodeId::Value(fragments_to_values(fragments, data_ctx)),
         id => id,
     };
 
-    Ok(vec![Node { id, kind, attributes, children: nodes }])
+    Ok(Node { id, kind, attributes, children, origin: widget_node.index })
+}
+
+// -----------------------------------------------------------------------------
+//     - Update nodes -
+//     Only evaluate the widget nodes that depend on the changed values,
+//     and clone the old nodes for everything else
+// -----------------------------------------------------------------------------
+pub(super) fn update_nodes(
+    widget_nodes: &[WidgetNode],
+    data_ctx: &SubContext<'_>,
+    node_ctx: &mut NodeCtx<'_>,
+    old_nodes: &[Node],
+    changed: &[Path],
+) -> Result<Vec<Node>> {
+    let mut nodes = vec![];
+
+    // Included nodes are numbered by their own template,
+    // so there is no telling which of the old nodes came from the include
+    if widget_nodes.iter().any(|node| matches!(node.stmt, Statement::Include { .. })) {
+        for widget_node in widget_nodes {
+            nodes.extend(to_nodes(widget_node, data_ctx, node_ctx)?);
+        }
+        return Ok(nodes);
+    }
+
+    let mut old_nodes = old_nodes.iter().peekable();
+    for widget_node in widget_nodes {
+        // Every node produced by the widget node (or its children) has an index
+        // less than or equal to the widget node, and greater than any previous sibling.
+        let mut previous = vec![];
+        while let Some(node) = old_nodes.next_if(|node| node.origin <= widget_node.index) {
+            previous.push(node);
+        }
+
+        if !widget_node.deps.changed(changed) {
+            if widget_node.deps.declares_style() {
+                declare_styles(widget_node, data_ctx, node_ctx)?;
+            }
+            nodes.extend(previous.into_iter().cloned());
+            continue;
+        }
+
+        match (&widget_node.stmt, &previous[..]) {
+            (Statement::Node { children }, [old]) if old.origin == widget_node.index => {
+                let children = update_nodes(children, data_ctx, node_ctx, &old.children, changed)?;
+                nodes.push(to_node(widget_node, children, data_ctx, node_ctx)?);
+            }
+            _ => nodes.extend(to_nodes(widget_node, data_ctx, node_ctx)?),
+        }
+    }
+
+    Ok(nodes)
+}
+
+// Add the styles declared by an unchanged widget node to the stylesheet,
+// without evaluating anything but the path to the style
+fn declare_styles(widget_node: &WidgetNode, data_ctx: &SubContext<'_>, node_ctx: &mut NodeCtx<'_>) -> Result<()> {
+    match &widget_node.stmt {
+        Statement::Node { children } => {
+            for child in children.iter().filter(|child| child.deps.declares_style()) {
+                declare_styles(child, data_ctx, node_ctx)?;
+            }
+        }
+        // Control flow provides the data for the children, so it's evaluated as a whole
+        _ => {
+            let _ = to_nodes(widget_node, data_ctx, node_ctx)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns `true` if a style, either in the template or the stylesheet, depends on any of the changed values.
+/// As a style applies to every node after it, this requires all nodes to be evaluated again.
+pub(super) fn style_changed(widget_nodes: &[WidgetNode], stylesheet: &Stylesheet, changed: &[Path]) -> bool {
+    let mut deps = Dependencies::default();
+    stylesheet.styles().for_each(|style| deps.attributes(style));
+    deps.changed(changed) || widget_nodes.iter().any(|node| node.deps.style_changed(changed))
+}
+
+// Create a stylesheet from widget nodes that are all style declarations
+pub(super) fn to_stylesheet(widget_nodes: Vec<WidgetNode>) -> Result<Stylesheet> {
+    let mut stylesheet = Stylesheet::default();
+    for widget_node in widget_nodes {
+        match widget_node.stmt {
+            Statement::Style { name } => stylesheet.insert(name, widget_node.attributes),
+            _ => return Err(Error::NotAStyle(widget_node.ident).at(&widget_node.span)),
+        }
+    }
+    Ok(stylesheet)
 }
 
 pub(super) fn to_nodes(
@@ -214,10 +347,16 @@ pub(super) fn to_nodes(
             }
 
             let path_buffer = path.path(data_ctx);
-            let widget_nodes = node_ctx.includes(path_buffer).unwrap();
+            let widget_nodes = node_ctx.includes(path_buffer).map_err(|e| e.at(&widget_node.span))?;
             node_ctx.include_depth += 1;
             super::to_nodes(&widget_nodes, data_ctx, node_ctx)
         }
+        Statement::With { bindings, children } => with_statement(data_ctx, node_ctx, bindings, children),
+        Statement::Style { name } => {
+            let attributes = lookup_attributes(&widget_node.attributes, data_ctx);
+            node_ctx.stylesheet.insert(name, attributes);
+            Ok(vec![])
+        }
         Statement::Node { children } => widget_node_to_nodes(widget_node, children, data_ctx, node_ctx),
     }
 }
@@ -227,7 +366,7 @@ pub(super) fn to_nodes(
 //     Lookup attributes by path,
 //     or get the value from a transition
 // -----------------------------------------------------------------------------
-fn lookup_attributes<'a>(attributes: &'a Attributes, ctx: &SubContext<'_>) -> Attributes {
+fn lookup_attributes(attributes: &Attributes, ctx: &SubContext<'_>) -> Attributes {
     let mut attr = attributes.clone();
     for (k, v) in attributes {
         // Path
@@ -262,15 +401,32 @@ fn fragments_to_values(fragments: &[Fragment], ctx: &SubContext<'_>) -> Value {
     Value::List(values)
 }
 
+fn fragments_to_string(fragments: &[Fragment], ctx: &SubContext<'_>) -> String {
+    let mut string = String::new();
+    for fragment in fragments {
+        match fragment {
+            Fragment::String(s) => string.push_str(s),
+            Fragment::Data(path) => {
+                if let Some(value) = ctx.by_path(path) {
+                    string.push_str(&value.to_string());
+                }
+            }
+        }
+    }
+    string
+}
+
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
+
     use super::*;
-    use crate::templates::ctx::DataCtx;
+    use crate::display::Color;
+    use crate::templates::ctx::{DataCtx, IncludeCache};
     use crate::widgets::Path;
 
     fn ctx(val: &Value) -> DataCtx {
-        let ctx = DataCtx::with_value("path", val.clone());
-        ctx
+        DataCtx::with_value("path", val.clone())
     }
 
     fn attributes() -> Attributes {
@@ -303,4 +459,229 @@ mod test {
 
         assert_eq!(attribs.get_value("thing").unwrap(), Value::from(1u64));
     }
+
+    #[test]
+    fn with_binding() {
+        let src = r#"
+        with [theme: {{ app.theme }}, greeting: "hello {{ app.name }}"]:
+            text [foreground: {{ theme.primary }}]: "{{ greeting }}"
+        "#;
+
+        let theme = HashMap::from([("primary".to_string(), Value::Color(Color::Red))]);
+        let app = HashMap::from([("theme".to_string(), Value::Map(theme)), ("name".to_string(), "Ted".into())]);
+        let ctx = DataCtx::with_value("app", Value::Map(app));
+        let ctx = SubContext::new(&ctx);
+        let mut include_cache = IncludeCache::default();
+        let mut node_ctx = NodeCtx::new(&mut include_cache);
+
+        let widget_nodes = crate::templates::parse(src).unwrap();
+        let nodes = crate::templates::to_nodes(&widget_nodes, &ctx, &mut node_ctx).unwrap();
+
+        let text = &nodes[0];
+        assert_eq!(text.attributes.foreground(), Some(Color::Red));
+        match &text.children[0].kind {
+            Kind::Span(text) => assert_eq!(text, "hello Ted"),
+            Kind::Node { .. } => panic!("expected a span"),
+        }
+    }
+
+    // All the span texts in order
+    fn texts(nodes: &[Node]) -> Vec<String> {
+        let mut all = vec![];
+        for node in nodes {
+            if let Kind::Span(text) = &node.kind {
+                all.push(text.clone());
+            }
+            all.extend(texts(&node.children));
+        }
+        all
+    }
+
+    #[test]
+    fn update_changed_nodes_only() {
+        let src = r#"
+        vstack:
+            text: "{{ a }}"
+            for [data: {{ list }}, binding: item]:
+                text [id: {{ item }}]: "{{ item }}"
+            text: "{{ b }}"
+        "#;
+
+        let mut ctx = DataCtx::empty();
+        ctx.insert("a", "a");
+        ctx.insert("b", "b");
+        ctx.insert("list", Value::List(vec![1u64.into()]));
+        let _ = ctx.diff();
+
+        let mut include_cache = IncludeCache::default();
+        let widget_nodes = crate::templates::parse(src).unwrap();
+        let old_nodes = {
+            let sub = SubContext::new(&ctx);
+            let mut node_ctx = NodeCtx::new(&mut include_cache);
+            crate::templates::to_nodes(&widget_nodes, &sub, &mut node_ctx).unwrap()
+        };
+
+        // `a` changes without a diff, so the first text is not evaluated again
+        ctx.set("a", "not a");
+        ctx.insert("b", "new b");
+        ctx.get_list_mut("list").unwrap().push(2u64.into());
+        let changed = ctx.take_changes();
+
+        let sub = SubContext::new(&ctx);
+        let mut node_ctx = NodeCtx::new(&mut include_cache);
+        let nodes = crate::templates::update_nodes(&widget_nodes, &sub, &mut node_ctx, &old_nodes, &changed).unwrap();
+        assert_eq!(texts(&nodes), vec!["a", "1", "2", "new b"]);
+
+        // The nodes that didn't change are kept as they are
+        let changes = diff::diff(&nodes[0], old_nodes.into_iter().next().unwrap());
+        assert!(changes.changes.is_empty());
+    }
+
+    #[test]
+    fn update_nodes_by_path() {
+        let src = r#"
+        vstack:
+            text: "{{ user.name }}"
+            text: "{{ user.age }}"
+        "#;
+
+        let user = HashMap::from([("name".to_string(), Value::from("bill")), ("age".to_string(), Value::from(1u64))]);
+        let mut ctx = DataCtx::with_value("user", Value::Map(user));
+        let _ = ctx.take_changes();
+
+        let mut include_cache = IncludeCache::default();
+        let widget_nodes = crate::templates::parse(src).unwrap();
+        let old_nodes = {
+            let sub = SubContext::new(&ctx);
+            let mut node_ctx = NodeCtx::new(&mut include_cache);
+            crate::templates::to_nodes(&widget_nodes, &sub, &mut node_ctx).unwrap()
+        };
+
+        // The age changes without a diff, so the second text is not evaluated again
+        if let Some(Value::Map(user)) = ctx.get_mut("user") {
+            user.insert("age".to_string(), Value::from(2u64));
+        }
+        ctx.set_path(&Path::from("user.name"), "ted");
+        let changed = ctx.take_changes();
+
+        let sub = SubContext::new(&ctx);
+        let mut node_ctx = NodeCtx::new(&mut include_cache);
+        let nodes = crate::templates::update_nodes(&widget_nodes, &sub, &mut node_ctx, &old_nodes, &changed).unwrap();
+        assert_eq!(texts(&nodes), vec!["ted", "1"]);
+    }
+
+    #[test]
+    fn update_nodes_after_style_change() {
+        let src = r#"
+        style [foreground: {{ color }}]: "highlight"
+        vstack:
+            text [class: "highlight"]: "a"
+        "#;
+
+        let mut ctx = DataCtx::empty();
+        ctx.insert("color", Value::Color(Color::Red));
+        let _ = ctx.diff();
+
+        let mut include_cache = IncludeCache::default();
+        let widget_nodes = crate::templates::parse(src).unwrap();
+        let old_nodes = {
+            let sub = SubContext::new(&ctx);
+            let mut node_ctx = NodeCtx::new(&mut include_cache);
+            crate::templates::to_nodes(&widget_nodes, &sub, &mut node_ctx).unwrap()
+        };
+
+        ctx.insert("color", Value::Color(Color::Blue));
+        let changed = ctx.take_changes();
+
+        let sub = SubContext::new(&ctx);
+        let mut node_ctx = NodeCtx::new(&mut include_cache);
+        let nodes = crate::templates::update_nodes(&widget_nodes, &sub, &mut node_ctx, &old_nodes, &changed).unwrap();
+        let style = nodes[0].children[0].children[0].attributes.style();
+        assert_eq!(style.fg, Some(Color::Blue));
+    }
+
+    #[test]
+    fn style_class() {
+        let src = r#"
+        style [foreground: red, bold: true]: "warning"
+        vstack:
+            text [class: "warning"]: "a"
+            text [class: "warning", foreground: blue]: "b"
+        "#;
+
+        let ctx = DataCtx::empty();
+        let ctx = SubContext::new(&ctx);
+        let mut include_cache = IncludeCache::default();
+        let mut node_ctx = NodeCtx::new(&mut include_cache);
+
+        let widget_nodes = crate::templates::parse(src).unwrap();
+        let nodes = crate::templates::to_nodes(&widget_nodes, &ctx, &mut node_ctx).unwrap();
+        assert_eq!(nodes.len(), 1);
+
+        let style = nodes[0].children[0].children[0].attributes.style();
+        assert_eq!(style.fg, Some(Color::Red));
+        assert!(style.attributes.contains(crate::display::Attributes::BOLD));
+
+        // Inline attributes take precedence over the class
+        let style = nodes[0].children[1].children[0].attributes.style();
+        assert_eq!(style.fg, Some(Color::Blue));
+        assert!(style.attributes.contains(crate::display::Attributes::BOLD));
+    }
+
+    #[test]
+    fn nested_style_keeps_parent() {
+        let src = r#"
+        vstack:
+            vstack [width: {{ width }}]:
+                style [foreground: red]: "warning"
+            text [class: "warning"]: "{{ a }}"
+        "#;
+
+        let mut ctx = DataCtx::empty();
+        ctx.insert("width", 1u64);
+        ctx.insert("a", "a");
+        let _ = ctx.diff();
+
+        let mut include_cache = IncludeCache::default();
+        let widget_nodes = crate::templates::parse(src).unwrap();
+        let old_nodes = {
+            let sub = SubContext::new(&ctx);
+            let mut node_ctx = NodeCtx::new(&mut include_cache);
+            crate::templates::to_nodes(&widget_nodes, &sub, &mut node_ctx).unwrap()
+        };
+
+        // `width` changes without a diff, so the vstack declaring the style is not evaluated again
+        ctx.set("width", 2u64);
+        ctx.insert("a", "new a");
+        let changed = ctx.take_changes();
+
+        let sub = SubContext::new(&ctx);
+        let mut node_ctx = NodeCtx::new(&mut include_cache);
+        let nodes = crate::templates::update_nodes(&widget_nodes, &sub, &mut node_ctx, &old_nodes, &changed).unwrap();
+        assert_eq!(nodes[0].children[0].attributes.get_int("width"), Some(1));
+
+        let text = &nodes[0].children[1];
+        assert_eq!(texts(&text.children), vec!["new a"]);
+        assert_eq!(text.children[0].attributes.style().fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn stylesheet() {
+        let stylesheet = Stylesheet::parse(r#"style [foreground: red, width: 3]: "warning""#).unwrap();
+
+        let ctx = DataCtx::empty();
+        let ctx = SubContext::new(&ctx);
+        let mut include_cache = IncludeCache::default();
+        let mut node_ctx = NodeCtx::new(&mut include_cache).with_stylesheet(stylesheet);
+
+        let widget_nodes = crate::templates::parse(r#"text [class: "warning"]: "a""#).unwrap();
+        let nodes = crate::templates::to_nodes(&widget_nodes, &ctx, &mut node_ctx).unwrap();
+
+        // Only the style is applied
+        assert_eq!(nodes[0].children[0].attributes.style().fg, Some(Color::Red));
+        assert!(!nodes[0].attributes.has("width"));
+
+        // A stylesheet can only contain styles
+        assert!(Stylesheet::parse(r#"text: "a""#).is_err());
+    }
 }
@@ -11,10 +11,12 @@ pub(crate) struct TemplateNode<'src> {
     pub(crate) text: Option<Text>,
     pub(crate) attributes: Attributes,
     pub(crate) children: Vec<TemplateNode<'src>>,
+    // Byte offset of the node in the source
+    pub(crate) pos: usize,
 }
 
 impl<'src> TemplateNode<'src> {
-    pub(crate) fn new(ident: &'src str, attributes: Vec<Attribute<'src>>, mut text: Option<Text>) -> Self {
+    pub(crate) fn new(ident: &'src str, attributes: Vec<Attribute<'src>>, mut text: Option<Text>, pos: usize) -> Self {
         let attributes = Attributes::from(attributes);
 
         // If this is a text node then take the text
@@ -23,14 +25,14 @@ impl<'src> TemplateNode<'src> {
         let children = (ident == "text")
             .then_some(())
             .and_then(|()| text.take())
-            .map(|text| vec![Self::span(text, attributes.take_style())])
+            .map(|text| vec![Self::span(text, attributes.take_style(), pos)])
             .unwrap_or_default();
 
-        Self { ident, attributes, children, text }
+        Self { ident, attributes, children, text, pos }
     }
 
-    fn span(text: Text, attributes: Attributes) -> Self {
-        Self { ident: "span", text: Some(text), attributes, children: vec![] }
+    fn span(text: Text, attributes: Attributes, pos: usize) -> Self {
+        Self { ident: "span", text: Some(text), attributes, children: vec![], pos }
     }
 
     fn add_child(&mut self, child: TemplateNode<'src>) {
@@ -0,0 +1,98 @@
+use super::widget::{Statement, WidgetNode};
+use crate::templates::error::Error;
+use crate::templates::lookup::WidgetLookup;
+use crate::templates::schema::{Children, Schema};
+
+// The widget the nodes are children of, and what children it accepts.
+type Parent<'a> = Option<(&'a str, Children)>;
+
+pub(crate) fn validate(nodes: &[WidgetNode], lookup: &WidgetLookup, parent: Parent<'_>, errors: &mut Vec<Error>) {
+    // Style declarations don't produce any nodes.
+    // How many nodes an `if` or a `for` produces depends on the data,
+    // so they are counted as one node, and the nodes inside them are checked on their own.
+    let mut child_nodes = nodes.iter().filter(|n| !matches!(n.stmt, Statement::Style { .. }));
+    if let Some((parent, Children::One)) = parent {
+        if let Some(second) = child_nodes.nth(1) {
+            errors.push(Error::TooManyChildren(parent.to_string()).at(&second.span));
+        }
+    }
+
+    for node in nodes {
+        match &node.stmt {
+            Statement::If { children, elses, .. } => {
+                validate(children, lookup, parent, errors);
+                for (_, children) in elses {
+                    validate(children, lookup, parent, errors);
+                }
+            }
+            Statement::For { template, .. } => validate(template, lookup, parent, errors),
+            Statement::With { children, .. } => validate(children, lookup, parent, errors),
+            // Included templates are validated when they are loaded
+            Statement::Include { .. } | Statement::Style { .. } => {}
+            Statement::Node { children } => validate_node(node, children, lookup, parent, errors),
+        }
+    }
+}
+
+fn validate_node(
+    node: &WidgetNode,
+    children: &[WidgetNode],
+    lookup: &WidgetLookup,
+    parent: Parent<'_>,
+    errors: &mut Vec<Error>,
+) {
+    let ident = node.ident.as_str();
+
+    let accepted = match parent {
+        Some((_, Children::None)) => false,
+        Some((_, Children::Spans)) => ident == "span",
+        _ => ident != "span",
+    };
+
+    if !accepted {
+        let parent = parent.map(|(p, _)| p).unwrap_or("the root");
+        let err = Error::InvalidChild { parent: parent.to_string(), child: ident.to_string() };
+        errors.push(err.at(&node.span));
+    }
+
+    let span_schema;
+    let schema = if ident == "span" {
+        span_schema = Schema::span();
+        &span_schema
+    } else if !lookup.contains(ident) {
+        errors.push(Error::UnregisteredWidget(ident.to_string()).at(&node.span));
+        return;
+    } else {
+        match lookup.schema(ident) {
+            Some(schema) => schema,
+            None => return validate(children, lookup, Some((ident, Children::Many)), errors),
+        }
+    };
+
+    // Sorted to report the errors in a predictable order
+    let mut attributes = node.attributes.inner.iter().collect::<Vec<_>>();
+    attributes.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (name, value) in attributes {
+        match schema.attribute(name) {
+            None => {
+                let err = Error::UnknownAttribute { widget: ident.to_string(), attribute: name.clone() };
+                errors.push(err.at(&node.span));
+            }
+            Some(kind) if !kind.accepts(value) => {
+                let err = Error::InvalidAttributeValue { attribute: name.clone(), expected: kind };
+                errors.push(err.at(&node.span));
+            }
+            Some(_) => {}
+        }
+    }
+
+    for name in schema.required_attributes() {
+        if !node.attributes.has(name) {
+            let err = Error::MissingAttribute { widget: ident.to_string(), attribute: name.to_string() };
+            errors.push(err.at(&node.span));
+        }
+    }
+
+    validate(children, lookup, Some((ident, schema.children())), errors);
+}
@@ -1,7 +1,10 @@
 use crate::widgets::{fields, Attributes, NodeId, Value};
 
+use std::sync::Arc;
+
+use super::deps::Dependencies;
 use super::template::TemplateNode;
-use crate::templates::error::{Error, Result};
+use crate::templates::error::{Error, Result, Span};
 use crate::templates::parser::Text;
 
 mod keywords {
@@ -10,9 +13,11 @@ mod keywords {
     pub(super) const ELSE: &str = "else";
     pub(super) const COND: &str = "cond";
     pub(super) const INCLUDE: &str = "include";
+    pub(super) const WITH: &str = "with";
+    pub(super) const STYLE: &str = "style";
 }
 
-static ID_EXCEMPT: &[&str] = &["span", "for", "if", "else", "elif", "include"];
+static ID_EXCEMPT: &[&str] = &["span", "for", "if", "else", "elif", "include", "with", "style"];
 
 #[derive(Debug, Clone)]
 pub(super) enum Statement {
@@ -20,6 +25,8 @@ pub(super) enum Statement {
     If { children: Vec<WidgetNode>, cond: Value, elses: Vec<(Option<Value>, Vec<WidgetNode>)> },
     For { binding: Value, data: Value, template: Vec<WidgetNode> },
     Include { path: Text },
+    With { bindings: Vec<(String, Value)>, children: Vec<WidgetNode> },
+    Style { name: String },
 }
 
 // Note:
@@ -35,28 +42,50 @@ pub struct WidgetNode {
     pub(super) attributes: Attributes,
     pub(super) stmt: Statement,
     pub(super) node_id: NodeId,
+    pub(super) span: Span,
+    // Widget nodes are numbered in post-order, so the index is larger than the index
+    // of any of the children, and smaller than the index of any sibling that comes after.
+    pub(super) index: usize,
+    pub(super) deps: Dependencies,
 }
 
 impl WidgetNode {
     pub(crate) fn node_id(&self) -> NodeId {
         self.node_id.clone()
     }
+
+    /// The location of the node in the template
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
-pub(crate) fn to_widget_nodes(node_tree: Vec<TemplateNode<'_>>, needs_id: bool) -> Result<Vec<WidgetNode>> {
+fn deps(nodes: &[WidgetNode], deps: &mut Dependencies) {
+    nodes.iter().for_each(|node| deps.merge(&node.deps));
+}
+
+pub(crate) fn to_widget_nodes(
+    node_tree: Vec<TemplateNode<'_>>,
+    needs_id: bool,
+    src: &Arc<str>,
+    index: &mut usize,
+) -> Result<Vec<WidgetNode>> {
     let mut nodes = Vec::with_capacity(node_tree.len());
 
     let mut tree = node_tree.into_iter().peekable();
 
     while let Some(mut node) = tree.next() {
+        let span = Span::new(node.pos, src);
         let stmt = match node.ident {
             keywords::IF => {
-                let children = to_widget_nodes(node.children, true)?;
-                let cond = node.attributes.get_value(keywords::COND).ok_or(Error::MissingCondition)?;
+                let children = to_widget_nodes(node.children, true, src, index)?;
+                let cond =
+                    node.attributes.get_value(keywords::COND).ok_or_else(|| Error::MissingCondition.at(&span))?;
                 let mut elses = vec![];
 
                 while let Some(sib) = tree.next_if(|n| n.ident == keywords::ELSE) {
-                    elses.push((sib.attributes.get_value(keywords::COND), to_widget_nodes(sib.children, true)?));
+                    let cond = sib.attributes.get_value(keywords::COND);
+                    elses.push((cond, to_widget_nodes(sib.children, true, src, index)?));
                 }
 
                 Statement::If { children, cond, elses }
@@ -64,40 +93,105 @@ pub(crate) fn to_widget_nodes(node_tree: Vec<TemplateNode<'_>>, needs_id: bool)
             keywords::FOR => {
                 let binding = match node.attributes.get_value("binding") {
                     Some(binding @ Value::String(_)) => binding,
-                    _ => return Err(Error::BindingInvalidString),
+                    _ => return Err(Error::BindingInvalidString.at(&span)),
                 };
 
                 // Make sure `data` is a collection
                 let data = match node.attributes.get_value("data") {
                     Some(data @ Value::List(_)) => data,
                     Some(data @ Value::DataBinding(_)) => data,
-                    _ => return Err(Error::NonCollectionValue),
+                    _ => return Err(Error::NonCollectionValue.at(&span)),
                 };
 
-                let template = to_widget_nodes(node.children, true)?;
+                let template = to_widget_nodes(node.children, true, src, index)?;
                 Statement::For { binding, data, template }
             }
             keywords::INCLUDE => {
                 let path = match node.text.take() {
                     Some(data @ Text::String(_)) => data,
                     Some(data @ Text::Fragments(_)) => data,
-                    None => return Err(Error::MissingIncludePath),
+                    None => return Err(Error::MissingIncludePath.at(&span)),
                 };
                 Statement::Include { path }
             }
-            _ => Statement::Node { children: to_widget_nodes(node.children, needs_id)? },
+            keywords::WITH => {
+                // Every attribute (except the id) is a binding
+                // that is available to the children.
+                let _ = node.attributes.take_value(fields::ID);
+                let bindings = node.attributes.inner.drain().collect();
+                let children = to_widget_nodes(node.children, needs_id, src, index)?;
+                Statement::With { bindings, children }
+            }
+            keywords::STYLE => {
+                let name = match node.text.take() {
+                    Some(Text::String(name)) => name,
+                    _ => return Err(Error::MissingStyleName.at(&span)),
+                };
+                Statement::Style { name }
+            }
+            _ => Statement::Node { children: to_widget_nodes(node.children, needs_id, src, index)? },
         };
 
         let node_id = node.attributes.take_value(fields::ID);
         let node_id = match node_id {
             Some(val) => NodeId::Value(val), //NodeId::String(val.to_string()),
             // Some(Value::DataBinding(binding)) => ctx.by_path(binding),
-            None if !ID_EXCEMPT.contains(&node.ident) && needs_id => return Err(Error::MissingId),
+            None if !ID_EXCEMPT.contains(&node.ident) && needs_id => return Err(Error::MissingId.at(&span)),
             None => NodeId::auto(),
         };
 
-        let node =
-            WidgetNode { ident: node.ident.to_string(), text: node.text, node_id, attributes: node.attributes, stmt };
+        let mut node_deps = match stmt {
+            Statement::Include { .. } => Dependencies::always(),
+            _ => Dependencies::default(),
+        };
+        node_deps.attributes(&node.attributes);
+        if let NodeId::Value(value) = &node_id {
+            node_deps.value(value);
+        }
+        if let Some(text) = &node.text {
+            node_deps.text(text);
+        }
+        // The values controlling which children are evaluated, and with what data
+        let mut control_deps = Dependencies::default();
+        match &stmt {
+            Statement::Node { children } => deps(children, &mut node_deps),
+            Statement::If { children, cond, elses } => {
+                control_deps.value(cond);
+                deps(children, &mut node_deps);
+                for (cond, children) in elses {
+                    cond.iter().for_each(|cond| control_deps.value(cond));
+                    deps(children, &mut node_deps);
+                }
+            }
+            Statement::For { binding, data, template } => {
+                control_deps.value(binding);
+                control_deps.value(data);
+                deps(template, &mut node_deps);
+            }
+            Statement::With { bindings, children } => {
+                bindings.iter().for_each(|(_, value)| control_deps.value(value));
+                deps(children, &mut node_deps);
+            }
+            Statement::Include { .. } => {}
+            Statement::Style { .. } => node_deps.style(),
+        }
+        // A style declared by a child depends on the values controlling the children as well
+        if node_deps.declares_style() {
+            control_deps.style();
+        }
+        node_deps.merge(&control_deps);
+
+        *index += 1;
+        let node = WidgetNode {
+            ident: node.ident.to_string(),
+            text: node.text,
+            node_id,
+            attributes: node.attributes,
+            stmt,
+            span,
+            index: *index,
+            deps: node_deps,
+        };
 
         nodes.push(node);
     }
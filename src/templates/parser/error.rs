@@ -6,7 +6,7 @@ use super::lexer::TokenKind;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-fn src_line_no(end: usize, src: &str) -> (usize, usize) {
+pub(crate) fn src_line_no(end: usize, src: &str) -> (usize, usize) {
     let mut line_no = 1;
     let mut pos = 0;
 
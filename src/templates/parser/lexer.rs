@@ -23,6 +23,7 @@ pub(crate) enum TokenKind<'src> {
     LParen,
     RParen,
     String(&'src str),
+    Theme(&'src str),
     Whitespace(usize),
 }
 
@@ -107,6 +108,14 @@ impl<'src> Lexer<'src> {
             // -----------------------------------------------------------------------------
             _ if c.is_whitespace() && c != '\n' => Some(Ok(self.take_whitespace().to_token(index))),
 
+            // -----------------------------------------------------------------------------
+            //     - Theme values -
+            // -----------------------------------------------------------------------------
+            ('$', Some('a'..='z' | 'A'..='Z' | '_')) => {
+                let (start, _) = self.chars.next()?;
+                Some(Ok(TokenKind::Theme(self.take_ident(start)).to_token(index)))
+            }
+
             // -----------------------------------------------------------------------------
             //     - Hex values -
             // -----------------------------------------------------------------------------
@@ -275,6 +284,13 @@ mod test {
         actual
     }
 
+    #[test]
+    fn theme() {
+        let actual = token_kind("$primary-dark");
+        let expected = TokenKind::Theme("primary-dark");
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn comment() {
         let actual = token_kind("// hello world");
@@ -9,6 +9,7 @@ use crate::widgets::{Easing, Fragment, Number, Path, Value};
 
 use crate::templates::ctx::SubContext;
 use crate::templates::nodes::template::TemplateNode;
+use crate::templates::theme;
 
 use self::error::{Error, Result};
 use lexer::{Lexer, Meta, Token, TokenKind};
@@ -97,7 +98,7 @@ impl<'src> Parser<'src> {
             Err(e) => return Some(Err(e)),
         };
 
-        let (ident, indent) = match token.0 {
+        let (ident, indent, pos) = match token.0 {
             TokenKind::Whitespace(mut indent) => {
                 indent = match self.base_indent {
                     Some(base) => indent - base,
@@ -108,7 +109,7 @@ impl<'src> Parser<'src> {
                 };
 
                 match self.lexer.next()? {
-                    Ok(Token(TokenKind::Ident(ident), _)) => (ident, indent),
+                    Ok(Token(TokenKind::Ident(ident), meta)) => (ident, indent, meta.pos),
                     Ok(Token(TokenKind::Newline, _)) => return self.parse_node(),
                     Ok(Token(kind, meta)) => {
                         return Some(Err(Error::invalid_token(token.1.pos..meta.pos, self.src, kind, "ident")))
@@ -120,7 +121,7 @@ impl<'src> Parser<'src> {
                 if self.base_indent.is_none() {
                     self.base_indent = Some(0);
                 }
-                (ident, 0)
+                (ident, 0, token.1.pos)
             }
             kind => {
                 let end = match self.lexer.next() {
@@ -152,7 +153,7 @@ impl<'src> Parser<'src> {
             Err(e) => return Some(Err(e)),
         };
 
-        Some(Ok((indent, TemplateNode::new(ident, attributes, text))))
+        Some(Ok((indent, TemplateNode::new(ident, attributes, text, pos))))
     }
 
     fn consume_comment(&mut self) -> bool {
@@ -308,6 +309,7 @@ impl<'src> Parser<'src> {
                 Text::Fragments(fragments) => Ok(Value::Fragments(fragments)),
             },
             Some(Ok(Token(TokenKind::Hex(r, g, b), _))) => Ok(Value::Color(Color::Rgb { r, g, b })),
+            Some(Ok(Token(TokenKind::Theme(name), _))) => Ok(Value::DataBinding(theme::path(name))),
             Some(Ok(Token(TokenKind::Ident(b @ "true" | b @ "false"), _))) => {
                 match b {
                     "true" => Ok(Value::Bool(true)),
@@ -591,12 +593,8 @@ fn parse_path(lexer: &mut Peekable<Lexer<'_>>, ident: &str) -> Result<Path> {
     let mut path = Path::new(ident);
 
     let mut stack = Vec::new();
-    loop {
-        if let Some(Ok(Token(TokenKind::Fullstop, _))) = lexer.peek() {
-            lexer.next();
-        } else {
-            break;
-        }
+    while let Some(Ok(Token(TokenKind::Fullstop, _))) = lexer.peek() {
+        lexer.next();
 
         match lexer.next() {
             Some(Ok(Token(TokenKind::Ident(ident), _))) => stack.push(Path::new(ident)),
@@ -618,6 +616,7 @@ fn parse_path(lexer: &mut Peekable<Lexer<'_>>, ident: &str) -> Result<Path> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::display::UnderlineStyle;
     use crate::templates::parser::error::ErrorKind;
     use crate::widgets::Attributes;
 
@@ -776,6 +775,12 @@ mod test {
         assert_eq!(attribs.alignment(), Some(Align::TopRight));
     }
 
+    #[test]
+    fn parse_theme_colour() {
+        let attribs = parse_attributes("widget [foreground: $primary]:");
+        assert_eq!(attribs.get_data("foreground"), Some(&theme::path("primary")));
+    }
+
     #[test]
     fn parse_colours() {
         let attribs =
@@ -826,6 +831,18 @@ mod test {
         assert_eq!(attribs.border_style(), &BorderStyle::Custom("01234567".to_string()));
     }
 
+    #[test]
+    fn extended_style_attributes() {
+        let attribs = parse_attributes("text [underline-style: curly, underline-color: red, rapid-blink: true]:");
+        let style = attribs.style();
+        assert_eq!(style.underline_style(), Some(UnderlineStyle::Curly));
+        assert_eq!(style.underline_color, Some(Color::Red));
+        assert!(style.attributes.contains(crate::display::Attributes::RAPID_BLINK));
+
+        let attribs = parse_attributes("text [underlined: true, underline-style: none]:");
+        assert_eq!(attribs.style().underline_style(), None);
+    }
+
     #[test]
     fn word_wrap() {
         let attribs = parse_attributes("text [wrap: word]:");
@@ -841,19 +858,19 @@ mod test {
     #[test]
     fn whitespace_attribs() {
         // Trim start
-        assert_eq!(parse_attributes("text [trim-start: true]:").trim_start(), true);
-        assert_eq!(parse_attributes("text:").trim_start(), true);
-        assert_eq!(parse_attributes("text [trim-start: false]:").trim_start(), false);
+        assert!(parse_attributes("text [trim-start: true]:").trim_start());
+        assert!(parse_attributes("text:").trim_start());
+        assert!(!parse_attributes("text [trim-start: false]:").trim_start());
 
         // Trim end
-        assert_eq!(parse_attributes("text [trim-end: true]:").trim_end(), true);
-        assert_eq!(parse_attributes("text:").trim_end(), true);
-        assert_eq!(parse_attributes("text [trim-end: false]:").trim_end(), false);
+        assert!(parse_attributes("text [trim-end: true]:").trim_end());
+        assert!(parse_attributes("text:").trim_end());
+        assert!(!parse_attributes("text [trim-end: false]:").trim_end());
 
         // Collapse spaces
-        assert_eq!(parse_attributes("text [collapse-spaces: true]:").collapse_spaces(), true);
-        assert_eq!(parse_attributes("text:").collapse_spaces(), true);
-        assert_eq!(parse_attributes("text [collapse-spaces: false]:").collapse_spaces(), false);
+        assert!(parse_attributes("text [collapse-spaces: true]:").collapse_spaces());
+        assert!(parse_attributes("text:").collapse_spaces());
+        assert!(!parse_attributes("text [collapse-spaces: false]:").collapse_spaces());
     }
 
     #[test]
@@ -0,0 +1,231 @@
+//! Widget schemas.
+//!
+//! A [`Schema`] describes the attributes and children a widget accepts.
+//! Schemas are registered alongside the widget factory in the [`super::WidgetLookup`]
+//! and are used by [`super::validate`] to check a template before it's turned into widgets.
+use std::fmt;
+
+use crate::widgets::{fields, Value};
+
+/// Attributes that are accepted by every widget as they are consumed by the `WidgetContainer`.
+const COMMON: &[(&str, ValueKind)] = &[
+    (fields::ID, ValueKind::Any),
+    (fields::CLASS, ValueKind::String),
+    (fields::BACKGROUND, ValueKind::Color),
+    (fields::OPACITY, ValueKind::Number),
+    (fields::DISPLAY, ValueKind::Display),
+    (fields::PADDING, ValueKind::Number),
+    (fields::PADDING_TOP, ValueKind::Number),
+    (fields::PADDING_RIGHT, ValueKind::Number),
+    (fields::PADDING_BOTTOM, ValueKind::Number),
+    (fields::PADDING_LEFT, ValueKind::Number),
+];
+
+/// Attributes that make up a `Style`.
+const STYLE: &[(&str, ValueKind)] = &[
+    (fields::FOREGROUND, ValueKind::Color),
+    ("bold", ValueKind::Bool),
+    ("italic", ValueKind::Bool),
+    ("dim", ValueKind::Bool),
+    ("underlined", ValueKind::Bool),
+    ("overlined", ValueKind::Bool),
+    ("inverse", ValueKind::Bool),
+    ("crossed-out", ValueKind::Bool),
+    (fields::UNDERLINE_STYLE, ValueKind::String),
+    (fields::UNDERLINE_COLOR, ValueKind::Color),
+    ("blink", ValueKind::Bool),
+    ("rapid-blink", ValueKind::Bool),
+    ("hidden", ValueKind::Bool),
+];
+
+/// The kind of value an attribute expects.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ValueKind {
+    /// Any value is accepted.
+    Any,
+    /// [`Value::Alignment`]
+    Alignment,
+    /// [`Value::Bool`]
+    Bool,
+    /// [`Value::BorderStyle`]
+    BorderStyle,
+    /// [`Value::Color`]
+    Color,
+    /// [`Value::Direction`]
+    Direction,
+    /// [`Value::Display`]
+    Display,
+    /// [`Value::Number`]
+    Number,
+    /// [`Value::Sides`]
+    Sides,
+    /// [`Value::String`] or [`Value::Fragments`]
+    String,
+    /// [`Value::TextAlignment`]
+    TextAlignment,
+    /// [`Value::Wrap`]
+    Wrap,
+}
+
+impl ValueKind {
+    /// Returns true if the value is of this kind.
+    ///
+    /// Data bindings are only known once the template is evaluated,
+    /// and are always accepted.
+    /// A transition is checked against the value it's transitioning to.
+    pub fn accepts(&self, value: &Value) -> bool {
+        match (self, value) {
+            (Self::Any, _) | (_, Value::DataBinding(_)) => true,
+            (_, Value::Transition(value, ..)) => self.accepts(value),
+            (Self::Alignment, Value::Alignment(_))
+            | (Self::Bool, Value::Bool(_))
+            | (Self::BorderStyle, Value::BorderStyle(_))
+            | (Self::Color, Value::Color(_))
+            | (Self::Direction, Value::Direction(_))
+            | (Self::Display, Value::Display(_))
+            | (Self::Number, Value::Number(_))
+            | (Self::Sides, Value::Sides(_))
+            | (Self::String, Value::String(_) | Value::Fragments(_))
+            | (Self::TextAlignment, Value::TextAlignment(_))
+            | (Self::Wrap, Value::Wrap(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Any => "any value",
+            Self::Alignment => "alignment",
+            Self::Bool => "bool",
+            Self::BorderStyle => "border style",
+            Self::Color => "colour",
+            Self::Direction => "direction",
+            Self::Display => "display",
+            Self::Number => "number",
+            Self::Sides => "sides",
+            Self::String => "string",
+            Self::TextAlignment => "text alignment",
+            Self::Wrap => "wrap",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The children a widget accepts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Children {
+    /// No children.
+    None,
+    /// At most one child.
+    One,
+    /// Any number of children.
+    Many,
+    /// Only `span`s, e.g. the `text` widget.
+    Spans,
+}
+
+/// A single attribute in a [`Schema`].
+#[derive(Debug, Clone)]
+pub struct AttributeSchema {
+    /// Attribute name.
+    pub name: String,
+    /// The kind of value the attribute expects.
+    pub kind: ValueKind,
+    /// Is the attribute required.
+    pub required: bool,
+}
+
+/// Describes the attributes and children a widget accepts.
+///
+/// The attributes shared by all widgets (`id`, `class`, `background`, `display` and padding)
+/// are always accepted and don't have to be declared.
+///
+/// ```
+/// use anathema::templates::{Children, Schema, ValueKind};
+///
+/// let schema = Schema::new(Children::None)
+///     .required("value", ValueKind::Number)
+///     .optional("label", ValueKind::String);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub(crate) attributes: Vec<AttributeSchema>,
+    pub(crate) children: Children,
+}
+
+impl Schema {
+    /// Create a new schema without any attributes.
+    pub fn new(children: Children) -> Self {
+        Self { attributes: vec![], children }
+    }
+
+    /// Add an optional attribute.
+    pub fn optional(mut self, name: &str, kind: ValueKind) -> Self {
+        self.attributes.push(AttributeSchema { name: name.to_string(), kind, required: false });
+        self
+    }
+
+    /// Add a required attribute.
+    pub fn required(mut self, name: &str, kind: ValueKind) -> Self {
+        self.attributes.push(AttributeSchema { name: name.to_string(), kind, required: true });
+        self
+    }
+
+    /// Add all the style attributes (`foreground`, `bold`, `italic` etc.).
+    pub fn with_style(self) -> Self {
+        STYLE.iter().fold(self, |schema, (name, kind)| schema.optional(name, *kind))
+    }
+
+    /// The children accepted by the widget.
+    pub fn children(&self) -> Children {
+        self.children
+    }
+
+    /// Find the expected value kind of an attribute.
+    /// Returns `None` if the attribute is unknown to the widget.
+    pub fn attribute(&self, name: &str) -> Option<ValueKind> {
+        COMMON
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, kind)| *kind)
+            .or_else(|| self.attributes.iter().find(|a| a.name == name).map(|a| a.kind))
+    }
+
+    /// An iterator over the names of all required attributes.
+    pub fn required_attributes(&self) -> impl Iterator<Item = &str> {
+        self.attributes.iter().filter(|a| a.required).map(|a| a.name.as_str())
+    }
+
+    // Schema for a `span`, which is not a widget in itself but part of a `text` widget.
+    pub(crate) fn span() -> Self {
+        Self::new(Children::None).with_style().optional(fields::LINK, ValueKind::String)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::display::Color;
+
+    #[test]
+    fn value_kinds() {
+        assert!(ValueKind::Number.accepts(&Value::from(1u64)));
+        assert!(ValueKind::Number.accepts(&Value::DataBinding(crate::widgets::Path::new("x"))));
+        assert!(ValueKind::Color.accepts(&Value::Transition(
+            Box::new(Value::Color(Color::Red)),
+            std::time::Duration::from_millis(10),
+            Default::default()
+        )));
+        assert!(!ValueKind::Bool.accepts(&Value::from("true")));
+    }
+
+    #[test]
+    fn common_attributes() {
+        let schema = Schema::new(Children::None).optional(fields::WIDTH, ValueKind::Number);
+        assert_eq!(schema.attribute(fields::WIDTH), Some(ValueKind::Number));
+        assert_eq!(schema.attribute(fields::BACKGROUND), Some(ValueKind::Color));
+        assert_eq!(schema.attribute("bold"), None);
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use crate::display::Color;
+use crate::widgets::{Path, Value};
+
+#[cfg(feature = "serde-json")]
+use super::error::{Error, Result};
+
+/// The key used to store the theme in the [`DataCtx`](super::DataCtx).
+/// The `$` prevents the key from clashing with any user defined value,
+/// as it's not a valid identifier in a template.
+pub(crate) const THEME_KEY: &str = "$theme";
+
+/// Create a path to a value in the theme
+pub(crate) fn path(name: &str) -> Path {
+    let mut path = Path::new(THEME_KEY);
+    path.child = Some(Box::new(Path::new(name)));
+    path
+}
+
+/// A `Theme` is a set of named colours that can be referenced in templates
+/// with a `$` prefix:
+///
+/// ```text
+/// border [foreground: $primary, background: $background]:
+///     text [foreground: $text]: "Hi"
+/// ```
+///
+/// The theme is stored in the [`DataCtx`](super::DataCtx), so replacing the theme
+/// with [`DataCtx::set_theme`](super::DataCtx::set_theme) will update every widget that uses it.
+///
+/// ```
+/// use anathema::display::Color;
+/// use anathema::templates::{DataCtx, Theme};
+///
+/// let mut dark = Theme::new();
+/// dark.insert("primary", Color::Rgb { r: 0xff, g: 0x88, b: 0x00 });
+/// dark.insert("background", Color::Black);
+///
+/// let mut ctx = DataCtx::empty();
+/// ctx.set_theme(&dark);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Theme {
+    colors: HashMap<String, Color>,
+}
+
+impl Theme {
+    /// Create an empty theme
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a named colour.
+    /// If the name already exists the colour is replaced.
+    pub fn insert(&mut self, name: impl Into<String>, color: Color) {
+        self.colors.insert(name.into(), color);
+    }
+
+    /// Get a colour by name
+    pub fn get(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).copied()
+    }
+
+    /// Load a theme from a JSON object where every value is a colour,
+    /// either as a name or as a hex value:
+    ///
+    /// ```text
+    /// {
+    ///     "primary": "#ff8800",
+    ///     "background": "black"
+    /// }
+    /// ```
+    #[cfg(feature = "serde-json")]
+    pub fn from_json(json: &str) -> Result<Self> {
+        let values: HashMap<String, String> = serde_json::from_str(json)?;
+        let mut theme = Self::new();
+        for (name, color) in values {
+            match crate::widgets::colour_from_str(&color) {
+                Some(color) => theme.insert(name, color),
+                None => return Err(Error::InvalidColor(color)),
+            }
+        }
+        Ok(theme)
+    }
+}
+
+impl From<&Theme> for Value {
+    fn from(theme: &Theme) -> Self {
+        let colors = theme.colors.iter().map(|(k, v)| (k.clone(), Value::Color(*v))).collect();
+        Value::Map(colors)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::templates::{to_nodes, DataCtx, IncludeCache, NodeCtx, SubContext};
+
+    #[test]
+    fn resolve_theme_colour() {
+        let widget_nodes = crate::templates::parse("text [foreground: $primary]: \"hi\"").unwrap();
+
+        let mut theme = Theme::new();
+        theme.insert("primary", Color::Red);
+        let mut ctx = DataCtx::empty();
+        ctx.set_theme(&theme);
+
+        let mut include_cache = IncludeCache::default();
+        let mut node_ctx = NodeCtx::new(&mut include_cache);
+        let nodes = to_nodes(&widget_nodes, &SubContext::new(&ctx), &mut node_ctx).unwrap();
+        assert_eq!(nodes[0].attributes.foreground(), Some(Color::Red));
+
+        // Swap the theme
+        theme.insert("primary", Color::Blue);
+        ctx.set_theme(&theme);
+        assert!(!ctx.diff().is_empty());
+
+        let nodes = to_nodes(&widget_nodes, &SubContext::new(&ctx), &mut node_ctx).unwrap();
+        assert_eq!(nodes[0].attributes.foreground(), Some(Color::Blue));
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn theme_from_json() {
+        let theme = Theme::from_json(r##"{ "primary": "#ff8800", "background": "black" }"##).unwrap();
+        assert_eq!(theme.get("primary"), Some(Color::Rgb { r: 0xff, g: 0x88, b: 0x00 }));
+        assert_eq!(theme.get("background"), Some(Color::Black));
+
+        assert!(Theme::from_json(r#"{ "primary": "not a colour" }"#).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use super::DataCtx;
+use crate::widgets::Value;
+
+/// A Rust type that is mirrored in a [`DataCtx`].
+///
+/// This is usually derived (requires the `derive` feature), which generates
+/// setters that update both the field and the data context,
+/// so the change is picked up by the next rebuild:
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use anathema::templates::{DataCtx, ViewModel};
+///
+/// #[derive(ViewModel)]
+/// struct Model {
+///     name: String,
+///     data: DataCtx,
+/// }
+///
+/// let mut model = Model { name: "anathema".into(), data: DataCtx::empty() };
+/// model.sync();
+/// model.set_name("bob");
+/// assert_eq!(model.data.get_string("name").unwrap(), "bob");
+/// ```
+pub trait ViewModel {
+    /// The model as a [`Value::Map`], with one entry per field.
+    fn to_value(&self) -> Value;
+
+    /// Insert every field of the model at the root of the data context.
+    /// Nothing is inserted if [`Self::to_value`] is not a map.
+    fn insert_into(&self, data: &mut DataCtx) {
+        if let Value::Map(map) = self.to_value() {
+            data.insert_map(map);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod test {
+    use super::*;
+    use crate::runtime::{Event, UserModel};
+    use crate::templates::ViewModel;
+    use crate::widgets::{NodeId, Path, Text, Widget, WidgetContainer};
+
+    #[derive(ViewModel, Clone)]
+    struct Address {
+        street: String,
+    }
+
+    #[derive(ViewModel)]
+    #[view_model(message = String, event = Self::on_event)]
+    struct User {
+        name: String,
+        #[view_model(rename = "user-age")]
+        age: u64,
+        #[view_model(nested)]
+        address: Address,
+        addresses: Vec<Address>,
+        #[view_model(skip)]
+        events: usize,
+        data: DataCtx,
+    }
+
+    impl User {
+        fn new() -> Self {
+            let address = Address { street: "Road".into() };
+            let mut user = Self {
+                name: "Alice".into(),
+                age: 30,
+                addresses: vec![address.clone()],
+                address,
+                events: 0,
+                data: DataCtx::empty(),
+            };
+            user.sync();
+            user
+        }
+
+        fn on_event(&mut self, event: Event<String>, _: &mut WidgetContainer) {
+            self.events += 1;
+            if let Some(name) = event.user() {
+                self.set_name(name);
+            }
+        }
+    }
+
+    #[test]
+    fn to_value() {
+        let user = User::new();
+        let value = user.to_value();
+        let map = match value {
+            Value::Map(map) => map,
+            _ => panic!("expected a map"),
+        };
+
+        assert_eq!(map.len(), 4);
+        assert_eq!(map["name"], Value::from("Alice"));
+        assert_eq!(map["user-age"], Value::from(30u64));
+        assert_eq!(map["address"], Value::from(Address { street: "Road".into() }));
+        assert!(matches!(&map["addresses"], Value::List(list) if list.len() == 1));
+        assert!(!map.contains_key("events"));
+        assert!(!map.contains_key("data"));
+    }
+
+    #[test]
+    fn setters_update_data() {
+        let mut user = User::new();
+        user.data.take_changes();
+
+        user.set_age(31u64);
+        assert_eq!(user.age, 31);
+        assert_eq!(user.data.get_u64("user-age"), Some(&31));
+
+        user.update_address(|address| address.street = "Lane".into());
+        assert_eq!(user.address.street, "Lane");
+        let street = user.data.by_path(&Path::from("address.street"));
+        assert_eq!(street, Some(&Value::from("Lane")));
+
+        assert_eq!(user.data.take_changes(), vec![Path::from("user-age"), Path::from("address")]);
+    }
+
+    #[test]
+    fn user_model() {
+        let mut user = User::new();
+        let mut root = Text::new().into_container(NodeId::auto());
+        user.event(Event::User("Bob".to_string()), &mut root);
+
+        assert_eq!(user.events, 1);
+        assert_eq!(user.data().get_string("name").unwrap(), "Bob");
+    }
+}
@@ -46,6 +46,10 @@ impl Widget for Alignment {
         self
     }
 
+    fn needs_paint(&self) -> bool {
+        false
+    }
+
     fn layout(&mut self, ctx: LayoutCtx) -> Size {
         let constraints = ctx.padded_constraints();
         // -----------------------------------------------------------------------------
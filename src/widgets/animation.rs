@@ -1,14 +1,15 @@
-use std::ops::{Add, Mul, Sub};
 use std::time::Duration;
 
 use super::attributes::Attributes;
-use super::value::Easing;
+use super::value::{Easing, Value};
 use super::Pos;
+use crate::display::{mix, to_rgb, Color};
 
 /// The animation context holds the animation for the [`crate::WidgetContainer`].
 #[derive(Debug)]
 pub struct AnimationCtx {
     transitions: Vec<(String, Animation<f32>)>,
+    colors: Vec<(String, Animation<Color>)>,
     position: Option<Animation<Pos>>,
 }
 
@@ -22,9 +23,19 @@ impl AnimationCtx {
         self.transitions.push((key, animation));
     }
 
+    /// Add a new colour transition to the list of colour transitions
+    /// If the key already exists it's replaced
+    pub fn push_color(&mut self, key: impl Into<String>, animation: Animation<Color>) {
+        let key = key.into();
+        // Remove old animation if one exists
+        self.remove(&key);
+        self.colors.push((key, animation));
+    }
+
     /// Remove a transition with a given key if it exists
     pub fn remove(&mut self, key: impl AsRef<str>) {
         self.transitions.retain(|(k, _)| key.as_ref().ne(k));
+        self.colors.retain(|(k, _)| key.as_ref().ne(k));
     }
 
     /// Update all transitions with the new time delta.
@@ -33,9 +44,12 @@ impl AnimationCtx {
         self.transitions.iter_mut().for_each(|(_, a)| {
             let _ = a.update(elapsed);
         });
-        self.position.as_mut().map(|a| {
-            a.update(elapsed);
+        self.colors.iter_mut().for_each(|(_, a)| {
+            let _ = a.update(elapsed);
         });
+        if let Some(a) = self.position.as_mut() {
+            a.update(elapsed);
+        }
     }
 
     /// Set the position for the position transition (which is a unique transition that operates on
@@ -47,8 +61,15 @@ impl AnimationCtx {
         }
     }
 
+    /// Returns `true` if any of the transitions are running.
+    pub fn is_active(&self) -> bool {
+        self.transitions.iter().any(|(_, a)| a.running())
+            || self.colors.iter().any(|(_, a)| a.running())
+            || self.position.as_ref().map(Animation::running).unwrap_or(false)
+    }
+
     pub(super) fn new() -> Self {
-        Self { transitions: vec![], position: None }
+        Self { transitions: vec![], colors: vec![], position: None }
     }
 
     pub(super) fn update_dst(&mut self, key: &str, val: f32) -> bool {
@@ -60,6 +81,15 @@ impl AnimationCtx {
         }
     }
 
+    pub(super) fn update_color_dst(&mut self, key: &str, val: Color) -> bool {
+        if let Some((_, anim)) = self.colors.iter_mut().find(|(k, _)| k.eq(key)) {
+            anim.set_dst(val);
+            true
+        } else {
+            false
+        }
+    }
+
     pub(super) fn attributes(&self) -> Attributes {
         let mut attributes = Attributes::empty();
 
@@ -67,6 +97,10 @@ impl AnimationCtx {
             attributes.set(k, val as i64);
         }
 
+        for (k, val) in self.colors.iter().filter_map(|(k, a)| a.get_value().map(|v| (k, v))) {
+            attributes.set(k, Value::Color(val));
+        }
+
         attributes
     }
 
@@ -132,6 +166,11 @@ impl<T: PartialEq> Animation<T> {
         self.elapsed < self.duration
     }
 
+    // The animation is moving towards a destination
+    fn running(&self) -> bool {
+        self.dst.is_some() && self.active()
+    }
+
     fn update(&mut self, delta: Duration) -> Option<()> {
         self.active().then_some(())?;
         self.elapsed += delta;
@@ -139,10 +178,7 @@ impl<T: PartialEq> Animation<T> {
     }
 }
 
-impl<T> Animation<T>
-where
-    T: std::fmt::Debug + Copy + PartialEq + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
-{
+impl<T: Interpolate> Animation<T> {
     fn get_value(&self) -> Option<T> {
         if !self.active() {
             return self.dst;
@@ -153,6 +189,71 @@ where
         let src = self.src?;
         let dst = self.dst?;
 
-        Some(src + (dst - src) * time)
+        Some(T::interpolate(src, dst, time))
+    }
+}
+
+/// A value that can be animated, by interpolating between a source and a destination.
+pub trait Interpolate: Copy + PartialEq {
+    /// The value at `time` between `src` and `dst`, where `time` is `0.0` at the
+    /// source and `1.0` at the destination.
+    fn interpolate(src: Self, dst: Self, time: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(src: Self, dst: Self, time: f32) -> Self {
+        src + (dst - src) * time
+    }
+}
+
+impl Interpolate for Pos {
+    fn interpolate(src: Self, dst: Self, time: f32) -> Self {
+        src + (dst - src) * time
+    }
+}
+
+/// Colours are interpolated by their RGB value.
+/// A `Color::Reset` can't be interpolated, so it changes once the transition is done.
+impl Interpolate for Color {
+    fn interpolate(src: Self, dst: Self, time: f32) -> Self {
+        match (to_rgb(src), to_rgb(dst)) {
+            (Some(_), Some(_)) => mix(Some(src), dst, time),
+            _ if time < 1.0 => src,
+            _ => dst,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn color_transition() {
+        let black = Color::Rgb { r: 0, g: 0, b: 0 };
+        let white = Color::Rgb { r: 255, g: 255, b: 255 };
+
+        let mut ctx = AnimationCtx::new();
+        let mut animation = Animation::new(Duration::from_millis(100), Easing::Linear);
+        animation.set_src(black);
+        ctx.push_color("background", animation);
+
+        assert!(ctx.update_color_dst("background", white));
+        assert!(!ctx.update_color_dst("foreground", white));
+
+        ctx.update(Duration::from_millis(50));
+        let attributes = ctx.attributes();
+        assert_eq!(attributes.background(), Some(Color::Rgb { r: 128, g: 128, b: 128 }));
+
+        ctx.update(Duration::from_millis(50));
+        assert_eq!(ctx.attributes().background(), Some(white));
+    }
+
+    #[test]
+    fn color_reset_is_not_interpolated() {
+        let red = Color::Rgb { r: 255, g: 0, b: 0 };
+        assert_eq!(Color::interpolate(Color::Reset, red, 0.5), Color::Reset);
+        assert_eq!(Color::interpolate(Color::Reset, red, 1.0), red);
+        assert_eq!(Color::interpolate(Color::Black, Color::White, 0.5), Color::Rgb { r: 128, g: 128, b: 128 });
     }
 }
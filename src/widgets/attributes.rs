@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
-use crate::display::{Color, Style};
+use crate::display::{Color, Style, UnderlineStyle};
 
 use super::value::Path;
 use super::value::{Easing, Value};
@@ -19,6 +19,7 @@ pub mod fields {
     pub const BORDER_CHARS: &str = "border-chars";
     pub const BORDER_STYLE: &str = "border-style";
     pub const BOTTOM: &str = "bottom";
+    pub const CLASS: &str = "class";
     pub const COLLAPSE_SPACES: &str = "collapse-spaces";
     pub const MAX_HEIGHT: &str = "max-height";
     pub const MAX_WIDTH: &str = "max-width";
@@ -33,9 +34,11 @@ pub mod fields {
     pub const HEIGHT: &str = "height";
     pub const ID: &str = "id";
     pub const LEFT: &str = "left";
+    pub const LINK: &str = "link";
     pub const MAX_CHILDREN: &str = "max-children";
     pub const NAME: &str = "name";
     pub const OFFSET: &str = "offset";
+    pub const OPACITY: &str = "opacity";
     pub const PADDING: &str = "padding";
     pub const PADDING_TOP: &str = "padding-top";
     pub const PADDING_RIGHT: &str = "padding-right";
@@ -50,10 +53,30 @@ pub mod fields {
     pub const TOP: &str = "top";
     pub const TRIM_END: &str = "trim-end";
     pub const TRIM_START: &str = "trim-start";
+    pub const UNDERLINE_COLOR: &str = "underline-color";
+    pub const UNDERLINE_STYLE: &str = "underline-style";
     pub const WIDTH: &str = "width";
     pub const WRAP: &str = "wrap";
 }
 
+/// The attributes that make up the [`Style`] of a widget, see [`Attributes::style`].
+pub(crate) const STYLE_FIELDS: &[&str] = &[
+    fields::FOREGROUND,
+    fields::BACKGROUND,
+    "bold",
+    "italic",
+    "dim",
+    "underlined",
+    "overlined",
+    "inverse",
+    "crossed-out",
+    fields::UNDERLINE_STYLE,
+    fields::UNDERLINE_COLOR,
+    "blink",
+    "rapid-blink",
+    "hidden",
+];
+
 // -----------------------------------------------------------------------------
 //     - Attributes -
 // -----------------------------------------------------------------------------
@@ -104,6 +127,14 @@ impl Attributes {
         })
     }
 
+    /// Get all colour transitions.
+    pub fn color_transitions(&self) -> impl Iterator<Item = (&str, Color, Duration, Easing)> {
+        self.inner.iter().filter_map(|(k, v)| match v {
+            Value::Transition(val, duration, easing) => val.to_color().map(|val| (k.as_ref(), val, *duration, *easing)),
+            _ => None,
+        })
+    }
+
     pub fn has(&self, key: &str) -> bool {
         self.inner.contains_key(key)
     }
@@ -273,6 +304,28 @@ impl Attributes {
         self.get_color(fields::FOREGROUND)
     }
 
+    pub fn underline_color(&self) -> Option<Color> {
+        self.get_color(fields::UNDERLINE_COLOR)
+    }
+
+    /// The style of the underline: `single`, `double`, `curly`, `dotted`, `dashed` or `none`.
+    /// `None` if the attribute isn't set or isn't a valid style.
+    pub fn underline_style(&self) -> Option<Option<UnderlineStyle>> {
+        match self.get_str(fields::UNDERLINE_STYLE)? {
+            "none" => Some(None),
+            style => style.parse().ok().map(Some),
+        }
+    }
+
+    /// The opacity of the background in percent, from `0` (transparent) to `100` (opaque).
+    pub fn opacity(&self) -> Option<u8> {
+        self.get_int(fields::OPACITY).map(|o| o.min(100) as u8)
+    }
+
+    pub fn link(&self) -> Option<String> {
+        self.get_string(fields::LINK)
+    }
+
     pub fn max_children(&self) -> Option<usize> {
         self.get_int(fields::MAX_CHILDREN).map(|i| i as usize)
     }
@@ -333,6 +386,24 @@ impl Attributes {
             inst.set_crossed_out(true);
         }
 
+        if let Some(underline_style) = self.underline_style() {
+            inst.set_underline_style(underline_style);
+        }
+
+        inst.underline_color = self.underline_color();
+
+        if self.get_bool("blink").unwrap_or(false) {
+            inst.set_blink(true);
+        }
+
+        if self.get_bool("rapid-blink").unwrap_or(false) {
+            inst.set_rapid_blink(true);
+        }
+
+        if self.get_bool("hidden").unwrap_or(false) {
+            inst.set_hidden(true);
+        }
+
         inst
     }
 
@@ -368,48 +439,37 @@ impl Attributes {
         if self.has("crossed-out") {
             style.set_crossed_out(self.get_bool("crossed-out").unwrap_or(false));
         }
-    }
-
-    pub fn take_style(&self) -> Attributes {
-        let mut attributes = Attributes::empty();
-        // let mut inst = Style::new();
-        // inst.fg = self.foreground();
-        // inst.bg = self.background();
-
-        if let Some(color) = self.get_color("foreground") {
-            attributes.set("foreground", color);
-        }
 
-        if let Some(color) = self.get_color("background") {
-            attributes.set("background", color);
+        if self.has(fields::UNDERLINE_STYLE) {
+            style.set_underline_style(self.underline_style().flatten());
         }
 
-        if let Some(true) = self.get_bool("bold") {
-            attributes.set("bold", true);
+        if self.has(fields::UNDERLINE_COLOR) {
+            style.underline_color = self.underline_color();
         }
 
-        if let Some(true) = self.get_bool("italic") {
-            attributes.set("italic", true);
+        if self.has("blink") {
+            style.set_blink(self.get_bool("blink").unwrap_or(false));
         }
 
-        if let Some(true) = self.get_bool("dim") {
-            attributes.set("dim", true);
+        if self.has("rapid-blink") {
+            style.set_rapid_blink(self.get_bool("rapid-blink").unwrap_or(false));
         }
 
-        if let Some(true) = self.get_bool("underlined") {
-            attributes.set("underline", true);
-        }
-
-        if let Some(true) = self.get_bool("overlined") {
-            attributes.set("overlined", true);
+        if self.has("hidden") {
+            style.set_hidden(self.get_bool("hidden").unwrap_or(false));
         }
+    }
 
-        if let Some(true) = self.get_bool("inverse") {
-            attributes.set("inverse", true);
-        }
+    pub fn take_style(&self) -> Attributes {
+        let mut attributes = Attributes::empty();
 
-        if let Some(true) = self.get_bool("crossed-out") {
-            attributes.set("crossed-out", true);
+        // The values are copied as they are, so values bound to data
+        // (e.g `$primary` or `{{ color }}`) are resolved when the span is evaluated
+        for &name in STYLE_FIELDS.iter().chain(&[fields::LINK, fields::CLASS]) {
+            if let Some(value) = self.value(name) {
+                attributes.set(name, value.clone());
+            }
         }
 
         attributes
@@ -471,10 +531,7 @@ impl Attributes {
     }
 
     pub fn get_color(&self, name: &str) -> Option<Color> {
-        match self.value(name) {
-            Some(Value::Color(color)) => Some(*color),
-            None | Some(_) => None,
-        }
+        self.value(name)?.to_color()
     }
 }
 
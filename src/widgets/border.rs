@@ -231,6 +231,10 @@ impl Widget for Border {
         self
     }
 
+    fn needs_paint(&self) -> bool {
+        false
+    }
+
     fn layout(&mut self, mut ctx: LayoutCtx) -> Size {
         // If there is a min width / height, make sure the minimum constraints
         // are matching these
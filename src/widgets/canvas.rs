@@ -84,6 +84,7 @@ impl Canvas {
 
         let pos = ScreenPos::new(pos.x as u16, pos.y as u16);
         buffer.empty(pos);
+        self.needs_paint = true;
     }
 }
 
@@ -97,13 +98,11 @@ impl Widget for Canvas {
     }
 
     fn needs_layout(&mut self) -> bool {
-        // self.needs_layout
-        true
+        self.needs_layout
     }
 
     fn needs_paint(&self) -> bool {
-        // self.needs_paint
-        true
+        self.needs_paint
     }
 
     fn layout(&mut self, ctx: LayoutCtx) -> Size {
@@ -131,9 +130,9 @@ impl Widget for Canvas {
         self.needs_paint = false;
         for (y, line) in buffer.rows().enumerate() {
             for (x, cell) in line.enumerate() {
-                if let Some((c, style)) = cell {
+                if let Some((grapheme, style)) = cell {
                     let pos = LocalPos::new(x, y);
-                    ctx.put(c, style, pos);
+                    ctx.put_grapheme(grapheme, style, pos);
                 }
             }
         }
@@ -158,11 +157,15 @@ impl Widget for Canvas {
         let mut size = buf.size();
 
         if attributes.has(fields::WIDTH) {
-            attributes.width().map(|width| size.width = width);
+            if let Some(width) = attributes.width() {
+                size.width = width;
+            }
         }
 
         if attributes.has(fields::HEIGHT) {
-            attributes.height().map(|height| size.height = height);
+            if let Some(height) = attributes.height() {
+                size.height = height;
+            }
         }
 
         if buf.size() != size {
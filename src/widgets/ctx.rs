@@ -1,10 +1,11 @@
 use std::ops::Deref;
 
-use unicode_width::UnicodeWidthChar;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 use super::layout::{Constraints, Padding};
 use super::{Align, LocalPos, Pos, Region};
-use crate::display::{Screen, ScreenPos, Size, Style};
+use crate::display::{Color, Screen, ScreenPos, Size, Style};
 
 #[derive(Debug, Copy, Clone)]
 pub struct Unsized;
@@ -103,8 +104,8 @@ impl<'screen> PaintCtx<'screen, WithSize> {
     }
 
     pub fn print(&mut self, s: &str, style: Style, mut pos: LocalPos) -> Option<()> {
-        for c in s.chars() {
-            if let Some(p) = self.put(c, style, pos) {
+        for grapheme in s.graphemes(true) {
+            if let Some(p) = self.put_grapheme(grapheme, style, pos) {
                 pos = p;
             }
         }
@@ -114,12 +115,52 @@ impl<'screen> PaintCtx<'screen, WithSize> {
     // Place a char on the screen buffer, return
     // next cursor position in local space.
     //
-    // The `input_pos` is the position, in local space, where the character
+    // See `put_grapheme`.
+    pub fn put(&mut self, c: char, style: Style, input_pos: LocalPos) -> Option<LocalPos> {
+        self.put_grapheme(c.encode_utf8(&mut [0; 4]), style, input_pos)
+    }
+
+    // Place a grapheme cluster on the screen buffer, return
+    // next cursor position in local space.
+    //
+    // The `input_pos` is the position, in local space, where the grapheme
     // should be placed. This will (possibly) be offset if there is clipping available.
     //
     // The `outpout_pos` is the same as the `input_pos` unless clipping has been applied.
-    pub fn put(&mut self, c: char, style: Style, input_pos: LocalPos) -> Option<LocalPos> {
-        let width = c.width().unwrap_or(0);
+    pub fn put_grapheme(&mut self, grapheme: &str, style: Style, input_pos: LocalPos) -> Option<LocalPos> {
+        self.put_cell(grapheme, style, None, input_pos)
+    }
+
+    // Place a grapheme cluster on the screen buffer that links to `url`, return
+    // next cursor position in local space.
+    //
+    // See `put_grapheme`.
+    pub fn put_link(&mut self, grapheme: &str, style: Style, url: &str, input_pos: LocalPos) -> Option<LocalPos> {
+        self.put_cell(grapheme, style, Some(url), input_pos)
+    }
+
+    // Blend a background colour over what has already been painted at `pos`.
+    // The `opacity` goes from `0.0` to `1.0`.
+    //
+    // See [`crate::display::Buffer::blend_background`].
+    pub fn blend_background(&mut self, color: Color, opacity: f32, pos: LocalPos) {
+        if let Some(clip) = self.clip.as_ref() {
+            if !self.clip(pos, clip) {
+                return;
+            }
+        }
+
+        if !self.pos_inside_local_region(pos) {
+            return;
+        }
+
+        if let Some(screen_pos) = self.translate_to_screen(pos) {
+            self.screen.blend_background(color, opacity, screen_pos);
+        }
+    }
+
+    fn put_cell(&mut self, grapheme: &str, style: Style, link: Option<&str>, input_pos: LocalPos) -> Option<LocalPos> {
+        let width = grapheme.width();
         let next = LocalPos { x: input_pos.x + width, y: input_pos.y };
 
         // Ensure that the position is inside provided clipping region
@@ -130,28 +171,31 @@ impl<'screen> PaintCtx<'screen, WithSize> {
         }
 
         // 1. Newline (yes / no)
-        if c == '\n' {
+        if grapheme == "\n" || grapheme == "\r\n" {
             return self.newline(input_pos);
         }
 
-        // 2. Check if the char can be placed
+        // 2. Check if the grapheme can be placed
         if !self.pos_inside_local_region(input_pos) {
             return None;
         }
 
-        // 3. Place the char
+        // 3. Place the grapheme
         let screen_pos = self.translate_to_screen(input_pos)?;
-        self.screen.put(c, style, screen_pos);
+        match link {
+            Some(url) => self.screen.put_link(grapheme, style, url, screen_pos),
+            None => self.screen.put_grapheme(grapheme, style, screen_pos),
+        }
 
         // 4. Advance the cursor (which might trigger another newline)
         if input_pos.x >= self.local_size.width {
             self.newline(input_pos)
         } else {
-            Some(LocalPos { x: input_pos.x + width, y: input_pos.y })
+            Some(next)
         }
     }
 
-    pub fn sub_context<'a>(&'a mut self, clip: Option<&'a Region>) -> PaintCtx<'_, Unsized> {
+    pub fn sub_context<'a>(&'a mut self, clip: Option<&'a Region>) -> PaintCtx<'a, Unsized> {
         PaintCtx { screen: self.screen, clip, state: Unsized }
     }
 }
@@ -244,7 +288,7 @@ mod test {
         ctx.put('x', Style::reset(), LocalPos::new(1, 1));
 
         let (actual, _) = screen.buffer().get(ScreenPos::new(4, 3)).unwrap();
-        assert_eq!('x', actual);
+        assert_eq!("x", actual);
     }
 
     #[test]
@@ -266,7 +310,7 @@ mod test {
 
         let index: ScreenPos = (first + global_pos).try_into().unwrap();
         let (actual, _) = screen.buffer().get(index).unwrap();
-        assert_eq!('y', actual);
+        assert_eq!("y", actual);
 
         let index: ScreenPos = (second + global_pos).try_into().unwrap();
         assert!(screen.buffer().get(index).is_none());
@@ -292,4 +336,22 @@ mod test {
         assert!(screen.buffer().get(ScreenPos::new(2, 2)).is_none());
         assert!(screen.buffer().get(ScreenPos::new(100, 100)).is_none());
     }
+
+    #[test]
+    fn blend_background() {
+        let mut screen = Screen::new(&mut vec![], Size::new(3, 1)).unwrap();
+        let mut style = Style::new();
+        style.set_bg(Color::Rgb { r: 0, g: 0, b: 200 });
+        screen.put('a', style, ScreenPos::new(1, 0));
+
+        let mut ctx = PaintCtx::new(&mut screen, None).into_sized(Size::new(2, 1), Pos::new(1, 0));
+        ctx.blend_background(Color::Rgb { r: 200, g: 0, b: 0 }, 0.25, LocalPos::new(0, 0));
+        // Outside of the context
+        ctx.blend_background(Color::Rgb { r: 200, g: 0, b: 0 }, 0.25, LocalPos::new(2, 0));
+
+        let (c, style) = screen.get(ScreenPos::new(1, 0)).unwrap();
+        assert_eq!(c, "a");
+        assert_eq!(style.bg, Some(Color::Rgb { r: 50, g: 0, b: 150 }));
+        assert!(screen.get(ScreenPos::new(0, 0)).is_none());
+    }
 }
@@ -94,6 +94,10 @@ impl Widget for Expand {
         self
     }
 
+    fn needs_paint(&self) -> bool {
+        false
+    }
+
     fn layout(&mut self, ctx: LayoutCtx) -> Size {
         let mut size = match self.child {
             Some(ref mut child) => child.layout(ctx.padded_constraints(), ctx.force_layout),
@@ -1,5 +1,5 @@
 use crate::antstring::{AntString, Find};
-use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use unicode_width::UnicodeWidthStr;
 
 static WORD_BOUNDARIES: &[char] = &[' ', '\n'];
 
@@ -79,11 +79,13 @@ fn find_end_of_string<T>(input: &mut AntString<'_, T>, max_width: usize, word_wr
     let mut current_width = 0;
     let mut tracking_whitespace = false;
 
-    for (i, c) in input.char_indices() {
-        let char_len = c.len_utf8();
-        let char_width = c.width().unwrap_or(0);
+    // Iterate over grapheme clusters rather than chars, so a cluster
+    // (e.g a char with a combining accent) is never split over two lines
+    for (i, grapheme) in input.grapheme_indices() {
+        let grapheme_len = grapheme.len();
+        let grapheme_width = grapheme.width();
 
-        if c.is_whitespace() {
+        if grapheme.chars().all(char::is_whitespace) {
             // track whitespace
             tracking_whitespace = true;
             last_whitespace = Some(i);
@@ -92,7 +94,7 @@ fn find_end_of_string<T>(input: &mut AntString<'_, T>, max_width: usize, word_wr
             last_whitespace = Some(i);
         }
 
-        if current_width + char_width > max_width {
+        if current_width + grapheme_width > max_width {
             current_index = match (last_whitespace, word_wrap) {
                 (Some(last), true) => current_index.min(last),
                 (_, _) => current_index,
@@ -109,8 +111,8 @@ fn find_end_of_string<T>(input: &mut AntString<'_, T>, max_width: usize, word_wr
             break;
         }
 
-        current_width += char_width;
-        current_index += char_len;
+        current_width += grapheme_width;
+        current_index += grapheme_len;
     }
 
     current_index
@@ -190,7 +192,7 @@ impl TextLayout {
             let mut end_of_string = match self.wrap {
                 Wrap::Word => find_end_of_string(&mut input, self.max_width, true),
                 Wrap::Break => find_end_of_string(&mut input, self.max_width, false),
-                Wrap::NoWrap => input.len().min(self.max_width),
+                Wrap::NoWrap => find_end_of_string(&mut input, self.max_width, false),
             };
 
             if !self.ignore_newline {
@@ -435,6 +437,19 @@ mod test {
         assert_eq!(second, "🍅🍅🍅🍅🍅🍅🍅🍅🍅");
     }
 
+    #[test]
+    fn break_on_grapheme_clusters() {
+        let input = AntString::new(["e\u{301}e\u{301}e\u{301}👩‍👩‍👧‍👦"]);
+        let layout = TextLayout::new(Wrap::Break, 3);
+        let actual = layout.layout(input);
+        assert_eq!(actual[0].to_string(), "e\u{301}e\u{301}e\u{301}");
+        assert_eq!(actual[1].to_string(), "👩‍👩‍👧‍👦");
+
+        let input = AntString::new(["ab🇸🇪"]);
+        let layout = TextLayout::new(Wrap::NoWrap, 3);
+        assert_eq!(layout.layout(input)[0].to_string(), "ab");
+    }
+
     #[test]
     fn truncate_on_nowrap() {
         let input = AntString::new(["t", "oo long and", " lots of strings"]);
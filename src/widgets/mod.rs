@@ -25,11 +25,20 @@ pub(crate) mod zstack;
 #[cfg(test)]
 pub mod testing;
 
-pub use animation::{Animation, AnimationCtx};
+pub use animation::{Animation, AnimationCtx, Interpolate};
+#[cfg(feature = "templates")]
+pub(crate) use attributes::STYLE_FIELDS;
 pub use attributes::{fields, Attribute, Attributes};
 pub use ctx::{LayoutCtx, PaintCtx, PositionCtx, Unsized, WithSize};
 pub use id::NodeId;
 pub use value::{Easing, Fragment, Number, Path, Value};
+
+#[cfg(feature = "serde")]
+pub use value::serialize;
+pub use value::snapshot;
+
+#[cfg(feature = "serde-json")]
+pub(crate) use value::json::colour_from_str;
 pub use widget::{Widget, WidgetContainer};
 
 /// Determine how a widget should be displayed and laid out
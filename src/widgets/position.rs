@@ -105,6 +105,10 @@ impl Widget for Position {
         self
     }
 
+    fn needs_paint(&self) -> bool {
+        false
+    }
+
     fn layout(&mut self, ctx: LayoutCtx) -> Size {
         match self.child.as_mut() {
             Some(child) => {
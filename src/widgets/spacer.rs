@@ -31,6 +31,10 @@ impl Widget for Spacer {
         self
     }
 
+    fn needs_paint(&self) -> bool {
+        false
+    }
+
     fn layout(&mut self, ctx: LayoutCtx) -> Size {
         debug_assert!(
             ctx.constraints.is_width_tight() && ctx.constraints.is_height_tight(),
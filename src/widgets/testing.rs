@@ -1,3 +1,6 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
 use crate::display::{Screen, ScreenPos, Size};
 
 use super::layout::Constraints;
@@ -21,7 +24,7 @@ pub fn test_widget_container(mut widget: WidgetContainer, expected: &str) {
     let expected = lines.join("\n");
 
     // The size of the screen
-    let width = lines.iter().map(|s| s.chars().count()).max().unwrap();
+    let width = lines.iter().map(|s| s.width()).max().unwrap();
     let height = lines.len();
     let size = Size::new(width, height);
 
@@ -42,26 +45,36 @@ pub fn test_widget_container(mut widget: WidgetContainer, expected: &str) {
     // fails, as it;s possible to display the actual outcome
     let mut actual = String::new();
     for (y, line) in lines.iter().enumerate() {
-        for (x, _) in line.chars().enumerate() {
+        for (x, _) in graphemes(line) {
             let pos = ScreenPos::new(x as u16, y as u16);
-            if let Some((buffer_value, _)) = screen.get(pos) {
-                actual.push(buffer_value);
-            } else {
-                actual.push(' ');
+            match screen.get(pos) {
+                Some((buffer_value, _)) => actual.push_str(buffer_value),
+                None => actual.push(' '),
             }
         }
         actual.push('\n');
     }
 
     for (y, line) in lines.into_iter().enumerate() {
-        for (x, c) in line.chars().enumerate() {
+        for (x, grapheme) in graphemes(line) {
             let pos = ScreenPos::new(x as u16, y as u16);
             let buffer_value = screen.get(pos);
 
             match buffer_value {
-                Some((buf_char, _)) => assert_eq!(buf_char, c, "\nexpected:\n{}\nfound:\n{}", expected, actual),
-                None => assert_eq!(c, ' ', "expected:\n{}\nfound:\n{}", expected, actual),
+                Some((buf_grapheme, _)) => {
+                    assert_eq!(buf_grapheme, grapheme, "\nexpected:\n{}\nfound:\n{}", expected, actual)
+                }
+                None => assert_eq!(grapheme, " ", "expected:\n{}\nfound:\n{}", expected, actual),
             }
         }
     }
 }
+
+// Grapheme clusters and the column they start at
+fn graphemes(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.graphemes(true).scan(0, |x, grapheme| {
+        let col = *x;
+        *x += grapheme.width();
+        Some((col, grapheme))
+    })
+}
@@ -1,4 +1,4 @@
-use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use unicode_width::UnicodeWidthStr;
 
 use crate::antstring::AntString;
 use crate::display::{Size, Style};
@@ -143,8 +143,14 @@ impl Text {
 
     /// When diffing two text widgets, only update the spans that have changed.
     /// There should be no need to use this outside of the templates project.
-    pub fn update_span(&mut self, index: usize, text: Option<String>, style: Option<Style>) {
-        if text.is_none() && style.is_none() {
+    pub fn update_span(
+        &mut self,
+        index: usize,
+        text: Option<String>,
+        style: Option<Style>,
+        link: Option<Option<String>>,
+    ) {
+        if text.is_none() && style.is_none() && link.is_none() {
             return;
         }
 
@@ -158,6 +164,10 @@ impl Text {
             span.style = style;
         }
 
+        if let Some(link) = link {
+            span.link = link;
+        }
+
         self.needs_layout = true;
         self.needs_paint = true;
     }
@@ -179,6 +189,8 @@ impl Text {
     /// # }
     /// ```
     pub fn get_text_mut(&mut self, index: usize) -> Option<&mut String> {
+        self.needs_layout = true;
+        self.needs_paint = true;
         self.spans.get_mut(index).map(|span| &mut span.text)
     }
 
@@ -216,21 +228,18 @@ impl Widget for Text {
     // It makes no sense to check the "shape" of the text as that requires an
     // actual layout to happen, making this redundant
     fn needs_layout(&mut self) -> bool {
-        true
-        // self.needs_layout
+        self.needs_layout
     }
 
     fn needs_paint(&self) -> bool {
-        true
-        // self.needs_paint
+        self.needs_paint
     }
 
     fn layout(&mut self, ctx: LayoutCtx) -> Size {
         self.needs_layout = false;
         self.max_width = ctx.constraints.max_width;
 
-        let string_slices =
-            self.spans.iter().map(|span| (&span.style, span.text.as_str())).collect::<Vec<(&Style, &str)>>();
+        let string_slices = self.spans.iter().map(|span| (span, span.text.as_str())).collect::<Vec<_>>();
         let string = AntString::with_annotations(&string_slices);
 
         self.previous_width = string.width();
@@ -254,7 +263,7 @@ impl Widget for Text {
 
     fn paint(&mut self, mut ctx: PaintCtx<'_, WithSize>) {
         self.needs_paint = false;
-        let texts = self.spans.iter().map(|span| (&span.style, span.text.as_str())).collect::<Vec<(&Style, &str)>>();
+        let texts = self.spans.iter().map(|span| (span, span.text.as_str())).collect::<Vec<_>>();
 
         if texts.is_empty() {
             return;
@@ -280,9 +289,12 @@ impl Widget for Text {
             };
 
             let mut pos = LocalPos::new(x, y);
-            for (style, c) in string.annotated_chars() {
-                ctx.put(c, *style, pos);
-                pos.x += c.width().unwrap_or(0);
+            for (span, grapheme) in string.annotated_graphemes() {
+                match span.link {
+                    Some(ref url) => ctx.put_link(grapheme, span.style, url, pos),
+                    None => ctx.put_grapheme(grapheme, span.style, pos),
+                };
+                pos.x += grapheme.width();
             }
         }
     }
@@ -306,6 +318,9 @@ impl Widget for Text {
 
         if let Some(span) = self.spans.first_mut() {
             attributes.update_style(&mut span.style);
+            if attributes.has(fields::LINK) {
+                span.link = attributes.link();
+            }
         }
 
         for (k, _) in &attributes {
@@ -331,12 +346,15 @@ pub struct TextSpan {
     pub text: String,
     /// Style for the text
     pub style: Style,
+    /// A hyperlink (URL) for the text.
+    /// Clickable in terminals that support hyperlinks (OSC 8).
+    pub link: Option<String>,
 }
 
 impl TextSpan {
     /// Create a new instance of a text span
     pub fn new(text: impl AsRef<str>) -> Self {
-        Self { text: text.as_ref().to_owned(), style: Style::new() }
+        Self { text: text.as_ref().to_owned(), style: Style::new(), link: None }
     }
 }
 
@@ -349,7 +367,7 @@ impl<S: AsRef<str>> From<S> for TextSpan {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::display::Screen;
+    use crate::display::{Screen, ScreenPos};
     use crate::widgets::testing::test_widget;
     use crate::widgets::Constraints;
     use crate::widgets::{Align, Alignment, Border, BorderStyle, Padding, Pos, Sides};
@@ -387,6 +405,20 @@ mod test {
         );
     }
 
+    #[test]
+    fn grapheme_clusters() {
+        // A combining accent and a flag are single clusters
+        test_text(
+            Text::with_text("cafe\u{301} 🇸🇪 ok"),
+            r#"
+            ┌───────┐
+            │café 🇸🇪│
+            │ok     │
+            └───────┘
+            "#,
+        );
+    }
+
     #[test]
     fn word_wrap() {
         test_text(
@@ -524,8 +556,7 @@ mod test {
     fn word_wrap_no_space() {
         let constraint = Constraints::new(8, None);
         let text = "helloxhowareyou";
-        let mut text_widget = Text::default();
-        text_widget.spans = vec![TextSpan::new(text)];
+        let mut text_widget = Text { spans: vec![TextSpan::new(text)], ..Default::default() };
         let actual = text_widget.layout(LayoutCtx::new(constraint, false, Padding::ZERO));
         let expected = Size::new(8, 2);
         assert_eq!(actual, expected);
@@ -551,22 +582,37 @@ mod test {
     }
 
     #[test]
-    #[ignore]
     fn needs_layout_and_paint() {
         let mut screen = Screen::new(&mut vec![], Size::ZERO).unwrap();
 
         let mut text = Text::with_text("hi");
-        assert_eq!(text.needs_layout(), true);
-        assert_eq!(text.needs_paint(), true);
+        assert!(text.needs_layout());
+        assert!(text.needs_paint());
 
         text.layout(LayoutCtx::new(Constraints::unbounded(), false, Padding::ZERO));
         text.paint(PaintCtx::new(&mut screen, None).into_sized(Size::ZERO, Pos::ZERO));
-        assert_eq!(text.needs_layout(), false);
-        assert_eq!(text.needs_paint(), false);
+        assert!(!text.needs_layout());
+        assert!(!text.needs_paint());
 
         text.add_span("change");
-        assert_eq!(text.needs_layout(), true);
-        assert_eq!(text.needs_paint(), true);
+        assert!(text.needs_layout());
+        assert!(text.needs_paint());
+    }
+
+    #[test]
+    fn paint_link() {
+        let mut screen = Screen::new(&mut vec![], Size::new(8, 1)).unwrap();
+
+        let mut text = Text::with_text("go ");
+        let mut span = TextSpan::new("here");
+        span.link = Some("https://example.com".into());
+        text.add_span(span);
+
+        text.layout(LayoutCtx::new(Constraints::new(8, 1), false, Padding::ZERO));
+        text.paint(PaintCtx::new(&mut screen, None).into_sized(Size::new(8, 1), Pos::ZERO));
+
+        assert_eq!(screen.buffer().link(ScreenPos::new(0, 0)), None);
+        assert_eq!(screen.buffer().link(ScreenPos::new(3, 0)), Some("https://example.com"));
     }
 
     #[test]
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use crate::display::Color;
 
 use super::{Number, Value};
-use crate::widgets::{Align, Axis, BorderStyle, Display, Sides, Wrap};
+use crate::widgets::{Align, BorderStyle, Direction, Display, Sides, Wrap};
 
 #[cfg(feature = "serde-json")]
 impl From<serde_json::Value> for Value {
@@ -26,9 +26,7 @@ impl From<serde_json::Value> for Value {
             }
             serde_json::Value::String(value) if value.contains(|c: char| c.is_whitespace()) => Value::String(value),
             serde_json::Value::String(value) => value_from_json_string(value),
-            serde_json::Value::Array(values) => {
-                Value::List(values.into_iter().filter_map(|v| v.try_into().ok()).collect())
-            }
+            serde_json::Value::Array(values) => Value::List(values.into_iter().map(Value::from).collect()),
             serde_json::Value::Object(json_values) => {
                 let mut values = HashMap::<_, Value>::new();
 
@@ -54,9 +52,9 @@ fn value_from_json_string(s: String) -> Value {
         return Value::Alignment(align);
     }
 
-    // Try axis
-    if let Some(axis) = axis_from_str(&s) {
-        return Value::Axis(axis);
+    // Try direction
+    if let Some(direction) = direction_from_str(&s) {
+        return Value::Direction(direction);
     }
 
     // Try border style
@@ -82,12 +80,12 @@ fn value_from_json_string(s: String) -> Value {
     Value::String(s)
 }
 
-fn axis_from_str(s: &str) -> Option<Axis> {
+fn direction_from_str(s: &str) -> Option<Direction> {
     match s {
-        "horz" => Some(Axis::Horizontal),
-        "horizontal" => Some(Axis::Horizontal),
-        "vert" => Some(Axis::Vertical),
-        "vertical" => Some(Axis::Vertical),
+        "horz" => Some(Direction::Horizontal),
+        "horizontal" => Some(Direction::Horizontal),
+        "vert" => Some(Direction::Vertical),
+        "vertical" => Some(Direction::Vertical),
         _ => None,
     }
 }
@@ -107,7 +105,7 @@ fn alignment_from_str(s: &str) -> Option<Align> {
     }
 }
 
-fn colour_from_str(s: &str) -> Option<Color> {
+pub(crate) fn colour_from_str(s: &str) -> Option<Color> {
     if s.starts_with('#') && [4, 7].contains(&s.len()) {
         let hex = &s[1..];
         if hex.len() == 3 {
@@ -237,12 +235,12 @@ mod json_test {
     }
 
     #[test]
-    fn axis() {
+    fn direction() {
         let inputs = vec![
-            ("horz", Value::Axis(Axis::Horizontal)),
-            ("horizontal", Value::Axis(Axis::Horizontal)),
-            ("vert", Value::Axis(Axis::Vertical)),
-            ("vertical", Value::Axis(Axis::Vertical)),
+            ("horz", Value::Direction(Direction::Horizontal)),
+            ("horizontal", Value::Direction(Direction::Horizontal)),
+            ("vert", Value::Direction(Direction::Vertical)),
+            ("vertical", Value::Direction(Direction::Vertical)),
         ];
 
         for (input, expected) in inputs {
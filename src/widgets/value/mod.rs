@@ -11,6 +11,9 @@ use crate::widgets::Display;
 
 #[cfg(feature = "serde-json")]
 pub mod json;
+#[cfg(feature = "serde")]
+pub mod serialize;
+pub mod snapshot;
 
 /// A `Fragment` can be either a [`Path`] or a `String`.
 /// `Fragment`s are usually part of a list to represent a single string value.
@@ -30,7 +33,15 @@ impl Fragment {
 }
 
 /// A `Path` is used to look up a [`Value`] in a `crate::DataCtx`.
-#[derive(Debug, Clone, PartialEq, Hash)]
+///
+/// A number looks up a value in a list by index.
+///
+/// ```
+/// use anathema::widgets::Path;
+/// let path = Path::from("user.friends.0");
+/// assert_eq!(path.to_string(), "user.friends.0");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Path {
     /// Parent name
     pub name: String,
@@ -43,6 +54,24 @@ impl Path {
     pub fn new(name: &str) -> Self {
         Self { name: name.to_string(), child: None }
     }
+
+    /// Returns `true` if the paths are the same, or one of them is a parent of the other.
+    pub fn overlaps(&self, other: &Path) -> bool {
+        self.name == other.name
+            && match (&self.child, &other.child) {
+                (Some(lhs), Some(rhs)) => lhs.overlaps(rhs),
+                _ => true,
+            }
+    }
+}
+
+impl From<&str> for Path {
+    /// Create a path from a string where the names are separated by a `.`.
+    fn from(path: &str) -> Self {
+        let mut names = path.rsplit('.');
+        let last = Path::new(names.next().unwrap_or_default());
+        names.fold(last, |child, name| Path { name: name.to_string(), child: Some(Box::new(child)) })
+    }
 }
 
 impl fmt::Display for Path {
@@ -78,9 +107,10 @@ impl fmt::Display for Number {
 }
 
 /// Transition easing function.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub enum Easing {
     /// Linear easing function. This is the default one.
+    #[default]
     Linear,
     /// Ease in.
     EaseIn,
@@ -90,12 +120,6 @@ pub enum Easing {
     EaseInOut,
 }
 
-impl Default for Easing {
-    fn default() -> Self {
-        Self::Linear
-    }
-}
-
 impl Easing {
     pub(crate) fn apply(&self, time: f32) -> f32 {
         match self {
@@ -238,17 +262,35 @@ impl fmt::Display for Value {
 }
 
 impl Value {
-    /// Lookup a value inside a [`Value::Map`] using a [`Path`]
+    /// Lookup a value inside a [`Value::Map`] or [`Value::List`] using a [`Path`]
     pub fn lookup<'value>(path: &Path, data: &'value Value) -> Option<&'value Value> {
-        match data {
-            Value::Map(map) => match map.get(path.name.as_str())? {
-                data @ Value::Map(_) => match &path.child {
-                    Some(path) => Self::lookup(path, data),
-                    None => Some(data),
-                },
-                val => Some(val),
+        let value = match data {
+            Value::Map(map) => map.get(path.name.as_str())?,
+            Value::List(list) => match path.name.parse::<usize>() {
+                Ok(index) => list.get(index)?,
+                Err(_) => return Some(data),
             },
-            _ => Some(data),
+            _ => return Some(data),
+        };
+
+        match &path.child {
+            Some(path) => Self::lookup(path, value),
+            None => Some(value),
+        }
+    }
+
+    /// Lookup a mutable value inside a [`Value::Map`] or [`Value::List`] using a [`Path`].
+    /// Unlike [`Value::lookup`] every name in the path has to exist.
+    pub fn lookup_mut<'value>(path: &Path, data: &'value mut Value) -> Option<&'value mut Value> {
+        let value = match data {
+            Value::Map(map) => map.get_mut(path.name.as_str())?,
+            Value::List(list) => list.get_mut(path.name.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+
+        match &path.child {
+            Some(path) => Self::lookup_mut(path, value),
+            None => Some(value),
         }
     }
 
@@ -360,6 +402,10 @@ impl Value {
     pub fn to_color(&self) -> Option<Color> {
         match self {
             Self::Color(col) => Some(*col),
+            Self::Transition(value, _, _) => match value.as_ref() {
+                Self::Color(col) => Some(*col),
+                _ => None,
+            },
             _ => None,
         }
     }
@@ -0,0 +1,616 @@
+//! A text format for [`Value`]s, used to save application state between runs.
+//!
+//! Every variant has its own notation, so a value is restored exactly as it was saved:
+//!
+//! ```text
+//! empty
+//! true
+//! uint(1) int(-1) float(1.5)
+//! "a string with \"escaped\" characters\n"
+//! [uint(1), uint(2)]
+//! {"name": "Alice", "age": uint(42)}
+//! align(top-left) direction(vertical) display(show) text-align(centre) wrap(word)
+//! border-style(thin) border-style("0123456")
+//! color(red) color(#ff8800) color(208)
+//! sides(top|left)
+//! path("user.name")
+//! fragments("hello ", path("user.name"))
+//! transition(uint(10), 500000000, ease-in)
+//! ```
+//!
+//! A transition stores the duration in nanoseconds.
+//!
+//! A snapshot document is a list of `key = value` entries.
+//! Map keys are sorted, so the same values always produce the same text.
+//! Everything after a `//` is a comment.
+//!
+//! ```
+//! use anathema::widgets::snapshot::{from_document, to_document};
+//! use anathema::widgets::Value;
+//!
+//! let entries = vec![(Value::from("tab"), Value::from(2u64))];
+//! let text = to_document(&entries);
+//! assert_eq!(text, "\"tab\" = uint(2)\n");
+//! assert_eq!(from_document(&text).unwrap(), entries);
+//! ```
+use std::fmt::{self, Display, Write};
+use std::time::Duration;
+
+use super::{Easing, Fragment, Number, Path, Value};
+use crate::display::Color;
+use crate::widgets::{Align, BorderStyle, Direction, Display as WidgetDisplay, Sides, TextAlignment, Wrap};
+
+// -----------------------------------------------------------------------------
+//     - Error -
+// -----------------------------------------------------------------------------
+/// An error reading a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    /// Line number, starting at one.
+    pub line: usize,
+    msg: String,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid snapshot on line {}: {}", self.line, self.msg)
+    }
+}
+
+impl std::error::Error for Error {}
+
+// -----------------------------------------------------------------------------
+//     - Names -
+// -----------------------------------------------------------------------------
+const ALIGN: [(&str, Align); 9] = [
+    ("top-left", Align::TopLeft),
+    ("top", Align::Top),
+    ("top-right", Align::TopRight),
+    ("right", Align::Right),
+    ("bottom-right", Align::BottomRight),
+    ("bottom", Align::Bottom),
+    ("bottom-left", Align::BottomLeft),
+    ("left", Align::Left),
+    ("centre", Align::Centre),
+];
+
+const DIRECTION: [(&str, Direction); 2] = [("horizontal", Direction::Horizontal), ("vertical", Direction::Vertical)];
+
+const DISPLAY: [(&str, WidgetDisplay); 3] =
+    [("show", WidgetDisplay::Show), ("hide", WidgetDisplay::Hide), ("exclude", WidgetDisplay::Exclude)];
+
+const TEXT_ALIGN: [(&str, TextAlignment); 3] =
+    [("left", TextAlignment::Left), ("centre", TextAlignment::Centre), ("right", TextAlignment::Right)];
+
+const WRAP: [(&str, Wrap); 3] = [("no-wrap", Wrap::NoWrap), ("break", Wrap::Break), ("word", Wrap::Word)];
+
+const EASING: [(&str, Easing); 4] = [
+    ("linear", Easing::Linear),
+    ("ease-in", Easing::EaseIn),
+    ("ease-out", Easing::EaseOut),
+    ("ease-in-out", Easing::EaseInOut),
+];
+
+const SIDES: [(&str, Sides); 4] =
+    [("top", Sides::TOP), ("right", Sides::RIGHT), ("bottom", Sides::BOTTOM), ("left", Sides::LEFT)];
+
+const COLOR: [(&str, Color); 17] = [
+    ("reset", Color::Reset),
+    ("black", Color::Black),
+    ("dark-grey", Color::DarkGrey),
+    ("red", Color::Red),
+    ("dark-red", Color::DarkRed),
+    ("green", Color::Green),
+    ("dark-green", Color::DarkGreen),
+    ("yellow", Color::Yellow),
+    ("dark-yellow", Color::DarkYellow),
+    ("blue", Color::Blue),
+    ("dark-blue", Color::DarkBlue),
+    ("magenta", Color::Magenta),
+    ("dark-magenta", Color::DarkMagenta),
+    ("cyan", Color::Cyan),
+    ("dark-cyan", Color::DarkCyan),
+    ("white", Color::White),
+    ("grey", Color::Grey),
+];
+
+fn name_of<T: PartialEq>(names: &[(&'static str, T)], value: &T) -> &'static str {
+    names.iter().find(|(_, v)| v == value).map(|(name, _)| *name).expect("every variant has a name")
+}
+
+fn by_name<T: Clone>(names: &[(&str, T)], name: &str) -> Option<T> {
+    names.iter().find(|(n, _)| *n == name).map(|(_, value)| value.clone())
+}
+
+// -----------------------------------------------------------------------------
+//     - Write -
+// -----------------------------------------------------------------------------
+/// Write a value as text.
+pub fn to_string(value: &Value) -> String {
+    let mut output = String::new();
+    write_value(value, &mut output);
+    output
+}
+
+/// Write the entries as a snapshot document, one entry per line.
+pub fn to_document(entries: &[(Value, Value)]) -> String {
+    let mut output = String::new();
+    for (key, value) in entries {
+        write_value(key, &mut output);
+        output.push_str(" = ");
+        write_value(value, &mut output);
+        output.push('\n');
+    }
+    output
+}
+
+fn write_value(value: &Value, output: &mut String) {
+    // Writing to a string can't fail
+    let _ = match value {
+        Value::Empty => write!(output, "empty"),
+        Value::Bool(b) => write!(output, "{b}"),
+        Value::Number(Number::Unsigned(num)) => write!(output, "uint({num})"),
+        Value::Number(Number::Signed(num)) => write!(output, "int({num})"),
+        // Debug formatting keeps the precision
+        Value::Number(Number::Float(num)) => write!(output, "float({num:?})"),
+        Value::String(s) => write!(output, "{s:?}"),
+        Value::List(values) => {
+            output.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                write_value(value, output);
+            }
+            output.push(']');
+            Ok(())
+        }
+        Value::Map(map) => {
+            let mut keys = map.keys().collect::<Vec<_>>();
+            keys.sort();
+            output.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                let _ = write!(output, "{key:?}: ");
+                write_value(&map[key], output);
+            }
+            output.push('}');
+            Ok(())
+        }
+        Value::Alignment(align) => write!(output, "align({})", name_of(&ALIGN, align)),
+        Value::Direction(direction) => write!(output, "direction({})", name_of(&DIRECTION, direction)),
+        Value::Display(display) => write!(output, "display({})", name_of(&DISPLAY, display)),
+        Value::TextAlignment(align) => write!(output, "text-align({})", name_of(&TEXT_ALIGN, align)),
+        Value::Wrap(wrap) => write!(output, "wrap({})", name_of(&WRAP, wrap)),
+        Value::BorderStyle(BorderStyle::Thin) => write!(output, "border-style(thin)"),
+        Value::BorderStyle(BorderStyle::Thick) => write!(output, "border-style(thick)"),
+        Value::BorderStyle(BorderStyle::Custom(edges)) => write!(output, "border-style({edges:?})"),
+        Value::Color(Color::Rgb { r, g, b }) => write!(output, "color(#{r:02x}{g:02x}{b:02x})"),
+        Value::Color(Color::AnsiValue(ansi)) => write!(output, "color({ansi})"),
+        Value::Color(color) => write!(output, "color({})", name_of(&COLOR, color)),
+        Value::Sides(sides) => {
+            let names = SIDES.iter().filter(|(_, side)| sides.contains(*side)).map(|(name, _)| *name);
+            write!(output, "sides({})", names.collect::<Vec<_>>().join("|"))
+        }
+        Value::DataBinding(path) => write!(output, "path({:?})", path.to_string()),
+        Value::Fragments(fragments) => {
+            output.push_str("fragments(");
+            for (i, fragment) in fragments.iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                let _ = match fragment {
+                    Fragment::String(s) => write!(output, "{s:?}"),
+                    Fragment::Data(path) => write!(output, "path({:?})", path.to_string()),
+                };
+            }
+            output.push(')');
+            Ok(())
+        }
+        Value::Transition(value, duration, easing) => {
+            output.push_str("transition(");
+            write_value(value, output);
+            write!(output, ", {}, {})", duration.as_nanos(), name_of(&EASING, easing))
+        }
+    };
+}
+
+// -----------------------------------------------------------------------------
+//     - Read -
+// -----------------------------------------------------------------------------
+/// Read a value written with [`to_string`].
+pub fn from_str(input: &str) -> Result<Value, Error> {
+    let mut reader = Reader { input, pos: 0 };
+    let value = reader.value()?;
+    reader.end()?;
+    Ok(value)
+}
+
+/// Read the entries of a snapshot document written with [`to_document`].
+pub fn from_document(input: &str) -> Result<Vec<(Value, Value)>, Error> {
+    entries(input, Reader::value)
+}
+
+// Read a document where every key has to be a string
+#[cfg(feature = "templates")]
+pub(crate) fn from_named_document(input: &str) -> Result<Vec<(String, Value)>, Error> {
+    entries(input, Reader::string)
+}
+
+fn entries<'src, K>(
+    input: &'src str,
+    key: fn(&mut Reader<'src>) -> Result<K, Error>,
+) -> Result<Vec<(K, Value)>, Error> {
+    let mut reader = Reader { input, pos: 0 };
+    let mut entries = vec![];
+    while !reader.at_end() {
+        let key = key(&mut reader)?;
+        reader.expect('=')?;
+        let value = reader.value()?;
+        entries.push((key, value));
+    }
+    Ok(entries)
+}
+
+struct Reader<'src> {
+    input: &'src str,
+    pos: usize,
+}
+
+impl<'src> Reader<'src> {
+    fn error(&self, msg: impl Into<String>) -> Error {
+        let line = self.input[..self.pos].matches('\n').count() + 1;
+        Error { line, msg: msg.into() }
+    }
+
+    fn rest(&self) -> &'src str {
+        &self.input[self.pos..]
+    }
+
+    // Skip whitespace and comments
+    fn skip(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            match trimmed.starts_with("//") {
+                true => self.pos += trimmed.find('\n').unwrap_or(trimmed.len()),
+                false => break,
+            }
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip();
+        self.rest().is_empty()
+    }
+
+    fn end(&mut self) -> Result<(), Error> {
+        match self.at_end() {
+            true => Ok(()),
+            false => Err(self.error("expected the end of the input")),
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip();
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let next = self.peek() == Some(c);
+        if next {
+            self.pos += c.len_utf8();
+        }
+        next
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Error> {
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected `{c}`"))),
+        }
+    }
+
+    // A name, a number or anything else up to a delimiter
+    fn word(&mut self) -> &'src str {
+        self.skip();
+        let rest = self.rest();
+        let len = rest.find(|c: char| c.is_whitespace() || "()[]{},:=|\"".contains(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        match self.peek() {
+            Some('"') => return self.string().map(Value::String),
+            Some('[') => return self.list(),
+            Some('{') => return self.map(),
+            None => return Err(self.error("expected a value")),
+            _ => {}
+        }
+
+        let start = self.pos;
+        let name = self.word();
+        match name {
+            "empty" => return Ok(Value::Empty),
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            "" => return Err(self.error("expected a value")),
+            _ => {}
+        }
+
+        self.expect('(')?;
+        let value = match name {
+            "uint" => self.parse().map(|num| Value::Number(Number::Unsigned(num)))?,
+            "int" => self.parse().map(|num| Value::Number(Number::Signed(num)))?,
+            "float" => self.parse().map(|num| Value::Number(Number::Float(num)))?,
+            "align" => self.named(&ALIGN).map(Value::Alignment)?,
+            "direction" => self.named(&DIRECTION).map(Value::Direction)?,
+            "display" => self.named(&DISPLAY).map(Value::Display)?,
+            "text-align" => self.named(&TEXT_ALIGN).map(Value::TextAlignment)?,
+            "wrap" => self.named(&WRAP).map(Value::Wrap)?,
+            "border-style" => self.border_style().map(Value::BorderStyle)?,
+            "color" => self.color().map(Value::Color)?,
+            "sides" => self.sides().map(Value::Sides)?,
+            "path" => self.string().map(|path| Value::DataBinding(Path::from(path.as_str())))?,
+            "fragments" => self.fragments().map(Value::Fragments)?,
+            "transition" => self.transition()?,
+            _ => {
+                self.pos = start;
+                return Err(self.error(format!("unknown value `{name}`")));
+            }
+        };
+        self.expect(')')?;
+        Ok(value)
+    }
+
+    fn parse<T: std::str::FromStr>(&mut self) -> Result<T, Error> {
+        let word = self.word();
+        word.parse().map_err(|_| self.error(format!("invalid number `{word}`")))
+    }
+
+    fn named<T: Clone>(&mut self, names: &[(&str, T)]) -> Result<T, Error> {
+        let name = self.word();
+        by_name(names, name).ok_or_else(|| self.error(format!("unknown name `{name}`")))
+    }
+
+    fn border_style(&mut self) -> Result<BorderStyle, Error> {
+        if self.peek() == Some('"') {
+            return self.string().map(BorderStyle::Custom);
+        }
+        match self.word() {
+            "thin" => Ok(BorderStyle::Thin),
+            "thick" => Ok(BorderStyle::Thick),
+            name => Err(self.error(format!("unknown border style `{name}`"))),
+        }
+    }
+
+    fn color(&mut self) -> Result<Color, Error> {
+        let word = self.word();
+        if let Some(hex) = word.strip_prefix('#') {
+            let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb { r, g, b }),
+                _ => Err(self.error(format!("invalid colour `{word}`"))),
+            };
+        }
+
+        if let Ok(ansi) = word.parse() {
+            return Ok(Color::AnsiValue(ansi));
+        }
+
+        by_name(&COLOR, word).ok_or_else(|| self.error(format!("invalid colour `{word}`")))
+    }
+
+    fn sides(&mut self) -> Result<Sides, Error> {
+        let mut sides = Sides::EMPTY;
+        if self.peek() == Some(')') {
+            return Ok(sides);
+        }
+
+        loop {
+            sides |= self.named(&SIDES)?;
+            self.skip();
+            match self.rest().strip_prefix('|') {
+                Some(_) => self.pos += 1,
+                None => break Ok(sides),
+            }
+        }
+    }
+
+    fn fragments(&mut self) -> Result<Vec<Fragment>, Error> {
+        let mut fragments = vec![];
+        while self.peek() != Some(')') {
+            if !fragments.is_empty() {
+                self.expect(',')?;
+            }
+            let fragment = match self.value()? {
+                Value::String(s) => Fragment::String(s),
+                Value::DataBinding(path) => Fragment::Data(path),
+                _ => return Err(self.error("a fragment has to be a string or a path")),
+            };
+            fragments.push(fragment);
+        }
+        Ok(fragments)
+    }
+
+    fn transition(&mut self) -> Result<Value, Error> {
+        let value = self.value()?;
+        self.expect(',')?;
+        let nanos: u64 = self.parse()?;
+        self.expect(',')?;
+        let easing = self.named(&EASING)?;
+        Ok(Value::Transition(Box::new(value), Duration::from_nanos(nanos), easing))
+    }
+
+    fn list(&mut self) -> Result<Value, Error> {
+        self.expect('[')?;
+        let mut values = vec![];
+        while !self.eat(']') {
+            if !values.is_empty() {
+                self.expect(',')?;
+            }
+            values.push(self.value()?);
+        }
+        Ok(Value::List(values))
+    }
+
+    fn map(&mut self) -> Result<Value, Error> {
+        self.expect('{')?;
+        let mut map = std::collections::HashMap::new();
+        while !self.eat('}') {
+            if !map.is_empty() {
+                self.expect(',')?;
+            }
+            let key = self.string()?;
+            self.expect(':')?;
+            map.insert(key, self.value()?);
+        }
+        Ok(Value::Map(map))
+    }
+
+    // A string, using the same escapes as `{:?}`
+    fn string(&mut self) -> Result<String, Error> {
+        self.expect('"')?;
+        let mut output = String::new();
+        let mut chars = self.rest().char_indices();
+        loop {
+            let (i, c) = chars.next().ok_or_else(|| self.error("unterminated string"))?;
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    break Ok(output);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('0') => '\0',
+                        Some(c @ ('\\' | '"' | '\'')) => c,
+                        Some('u') => {
+                            let rest = chars.as_str();
+                            let code = rest
+                                .strip_prefix('{')
+                                .and_then(|rest| rest.split_once('}'))
+                                .and_then(|(hex, _)| u32::from_str_radix(hex, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?;
+                            // Skip past the closing brace
+                            for (_, c) in chars.by_ref() {
+                                if c == '}' {
+                                    break;
+                                }
+                            }
+                            code
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    output.push(escaped);
+                }
+                c => output.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn round_trip(value: Value) {
+        let text = to_string(&value);
+        assert_eq!(from_str(&text).unwrap(), value, "{text}");
+    }
+
+    #[test]
+    fn write_values() {
+        let map = Value::Map(HashMap::from([("b".to_string(), Value::Empty), ("a".to_string(), true.into())]));
+        assert_eq!(to_string(&map), r#"{"a": true, "b": empty}"#);
+
+        let sides = Value::Sides(Sides::TOP | Sides::LEFT);
+        assert_eq!(to_string(&sides), "sides(top|left)");
+
+        let color = Value::Color(Color::Rgb { r: 255, g: 136, b: 0 });
+        assert_eq!(to_string(&color), "color(#ff8800)");
+
+        let text = Value::from("say \"hi\"\n");
+        assert_eq!(to_string(&text), r#""say \"hi\"\n""#);
+    }
+
+    #[test]
+    fn round_trip_all_values() {
+        let values = vec![
+            Value::Empty,
+            Value::Bool(false),
+            Value::Number(Number::Unsigned(u64::MAX)),
+            Value::Number(Number::Signed(i64::MIN)),
+            Value::Number(Number::Float(0.1 + 0.2)),
+            Value::Number(Number::Float(-1.0)),
+            Value::from("multi\nline \"quoted\" \\ \u{1b}[0m ✓"),
+            Value::from(""),
+            Value::List(vec![]),
+            Value::Map(HashMap::new()),
+            Value::Alignment(Align::BottomRight),
+            Value::Direction(Direction::Vertical),
+            Value::Display(WidgetDisplay::Exclude),
+            Value::TextAlignment(TextAlignment::Centre),
+            Value::Wrap(Wrap::NoWrap),
+            Value::BorderStyle(BorderStyle::Thick),
+            Value::BorderStyle(BorderStyle::Custom("╭─╮│╯─╰│".into())),
+            Value::Color(Color::DarkMagenta),
+            Value::Color(Color::Rgb { r: 1, g: 2, b: 3 }),
+            Value::Color(Color::AnsiValue(208)),
+            Value::Sides(Sides::EMPTY),
+            Value::Sides(Sides::ALL),
+            Value::DataBinding(Path::from("user.friends.0")),
+            Value::Fragments(vec![Fragment::String("hi ".into()), Fragment::Data(Path::from("name"))]),
+            Value::Transition(Box::new(Value::from(10u64)), Duration::from_millis(1500), Easing::EaseInOut),
+        ];
+
+        let mut map = HashMap::new();
+        for (i, value) in values.iter().enumerate() {
+            map.insert(i.to_string(), value.clone());
+        }
+
+        values.iter().cloned().for_each(round_trip);
+        round_trip(Value::List(values));
+        round_trip(Value::Map(map));
+    }
+
+    #[test]
+    fn document() {
+        let input = r#"
+            // the selected tab
+            "tab" = uint(2)
+            "user" = {
+                "name": "Alice", // comment
+                "tags": ["a", "b"]
+            }
+        "#;
+
+        let entries = from_document(input).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], (Value::from("tab"), Value::from(2u64)));
+
+        let text = to_document(&entries);
+        assert_eq!(from_document(&text).unwrap(), entries);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let err = from_document("\"a\" = uint(1)\n\"b\" = uint(-1)").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        assert!(from_str("colour(red)").is_err());
+        assert!(from_str("color(#12345)").is_err());
+        assert!(from_str("\"unterminated").is_err());
+        assert!(from_str("[uint(1) uint(2)]").is_err());
+        assert!(from_str("uint(1) uint(2)").is_err());
+    }
+}
@@ -62,6 +62,10 @@ impl Widget for VStack {
         self
     }
 
+    fn needs_paint(&self) -> bool {
+        false
+    }
+
     fn layout(&mut self, mut ctx: LayoutCtx) -> Size {
         if let Some(width) = self.width {
             ctx.constraints.make_width_tight(width);
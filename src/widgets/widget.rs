@@ -6,7 +6,8 @@ use super::attributes::{fields, Attributes};
 use super::ctx::{LayoutCtx, PaintCtx, PositionCtx, Unsized, WithSize};
 use super::id::NodeId;
 use super::layout::{Constraints, Padding};
-use super::{AnimationCtx, Display, LocalPos, Pos, Region};
+use super::snapshot;
+use super::{AnimationCtx, Display, LocalPos, Pos, Region, Value};
 
 // Layout:
 // 1. Receive constraints
@@ -64,6 +65,14 @@ impl Widget for Box<dyn Widget> {
     fn update(&mut self, attributes: Attributes) {
         self.as_mut().update(attributes);
     }
+
+    fn save_state(&self) -> Option<Value> {
+        self.as_ref().save_state()
+    }
+
+    fn restore_state(&mut self, state: Value) {
+        self.as_mut().restore_state(state)
+    }
 }
 
 pub trait Widget: std::fmt::Debug + Send + Sync + 'static {
@@ -73,10 +82,18 @@ pub trait Widget: std::fmt::Debug + Send + Sync + 'static {
 
     fn as_any(&mut self) -> &mut dyn std::any::Any;
 
+    /// Return `true` if the widget has changed in a way that affects its size since the last layout.
+    /// A widget is always laid out again if the constraints change.
     fn needs_layout(&mut self) -> bool {
         true
     }
 
+    /// Return `true` if the widget has changed since it was last painted.
+    /// Children are checked separately.
+    ///
+    /// The runtime only renders a new frame if a widget needs to be painted,
+    /// an event was handled, the data context changed or an animation is running.
+    /// A widget that only changes through [`Widget::update`] can return `false`.
     fn needs_paint(&self) -> bool {
         true
     }
@@ -111,6 +128,18 @@ pub trait Widget: std::fmt::Debug + Send + Sync + 'static {
     fn remove_child(&mut self, child_id: &NodeId) -> Option<WidgetContainer>;
 
     fn update(&mut self, attributes: Attributes);
+
+    /// State that is not part of the template or the data context, e.g. a scroll offset,
+    /// to be saved with [`WidgetContainer::save_state`].
+    /// Only widgets with an id set in the template are saved.
+    fn save_state(&self) -> Option<Value> {
+        None
+    }
+
+    /// Restore the state returned from [`Widget::save_state`].
+    /// This is called on the widget with the same id, which could be a different kind of widget
+    /// if the template changed since the state was saved.
+    fn restore_state(&mut self, _state: Value) {}
 }
 
 /// The `WidgetContainer` has to go through three steps before it can be displayed:
@@ -125,9 +154,14 @@ pub struct WidgetContainer {
     pub padding: Padding,
     pub(crate) size: Size,
     pub background: Option<Color>,
+    /// The opacity of the background in percent.
+    /// Anything below `100` blends the background with what is already painted below it.
+    pub opacity: Option<u8>,
     pub animation: AnimationCtx,
     inner: Box<dyn Widget>,
     pos: Pos,
+    // The constraints and padding of the last layout
+    last_layout: Option<(Constraints, Padding)>,
 }
 
 impl WidgetContainer {
@@ -139,8 +173,10 @@ impl WidgetContainer {
             inner,
             pos: Pos::ZERO,
             background: None,
+            opacity: None,
             padding: Padding::ZERO,
             animation: AnimationCtx::new(),
+            last_layout: None,
         }
     }
 
@@ -204,38 +240,51 @@ impl WidgetContainer {
             if let Some(bottom) = attributes.padding_bottom() {
                 self.padding.bottom = bottom;
             }
+            if let Some(background) = attributes.background() {
+                self.background = Some(background);
+            }
+            if let Some(opacity) = attributes.opacity() {
+                self.opacity = Some(opacity);
+            }
         }
 
         self.inner.update(attributes);
     }
 
     pub fn layout(&mut self, mut constraints: Constraints, force_layout: bool) -> Size {
-        if self.inner.needs_layout() || force_layout {
-            match self.display {
-                Display::Exclude => self.size = Size::ZERO,
-                _ => {
-                    let padding = self
-                        .animation
-                        .get_value(fields::PADDING)
-                        .map(|p| Padding::new(p as usize))
-                        .unwrap_or(self.padding);
-
-                    self.animation.update_dst(fields::MAX_WIDTH, constraints.max_width as f32);
-                    constraints.max_width = self
-                        .animation
-                        .get_value(fields::MAX_WIDTH)
-                        .map(|val| val as usize)
-                        .unwrap_or(constraints.max_width);
-                    let ctx = LayoutCtx::new(constraints, force_layout, padding);
-                    let size = self.inner.layout(ctx);
-                    self.size = size;
-                }
-            }
+        if let Display::Exclude = self.display {
+            self.size = Size::ZERO;
+            self.last_layout = None;
+            return self.size;
+        }
+
+        let padding =
+            self.animation.get_value(fields::PADDING).map(|p| Padding::new(p as usize)).unwrap_or(self.padding);
+
+        self.animation.update_dst(fields::MAX_WIDTH, constraints.max_width as f32);
+        constraints.max_width =
+            self.animation.get_value(fields::MAX_WIDTH).map(|val| val as usize).unwrap_or(constraints.max_width);
+
+        let layout = Some((constraints, padding));
+        if self.inner.needs_layout() || force_layout || self.last_layout != layout {
+            self.last_layout = layout;
+            let ctx = LayoutCtx::new(constraints, force_layout, padding);
+            self.size = self.inner.layout(ctx);
         }
 
         self.size
     }
 
+    /// Returns `true` if this widget, or any of its children, needs to be painted.
+    pub fn needs_paint(&mut self) -> bool {
+        self.inner.needs_paint() || self.inner.children().into_iter().any(|child| child.needs_paint())
+    }
+
+    /// Returns `true` if this widget, or any of its children, has a running animation.
+    pub fn is_animating(&mut self) -> bool {
+        self.animation.is_active() || self.inner.children().into_iter().any(|child| child.is_animating())
+    }
+
     pub fn position(&mut self, pos: Pos) {
         self.animation.update_pos(self.pos, pos);
         self.pos = self.animation.get_pos().unwrap_or(pos);
@@ -260,6 +309,16 @@ impl WidgetContainer {
         let color = self.background?;
         let width = self.size.width;
 
+        if let Some(opacity) = self.opacity.filter(|opacity| *opacity < 100) {
+            let opacity = opacity as f32 / 100.0;
+            for y in 0..self.size.height {
+                for x in 0..width {
+                    ctx.blend_background(color, opacity, LocalPos::new(x, y));
+                }
+            }
+            return Some(());
+        }
+
         let background_str = format!("{:width$}", "", width = width);
         let mut style = Style::new();
         style.set_bg(color);
@@ -338,6 +397,28 @@ impl WidgetContainer {
         }
     }
 
+    /// Save the state of every widget with an id, see [`Widget::save_state`].
+    /// The state is restored with [`WidgetContainer::restore_state`],
+    /// e.g. once the widgets are created the next time the application starts.
+    ///
+    /// See [`snapshot`](crate::widgets::snapshot) for the format.
+    pub fn save_state(&mut self) -> String {
+        let mut entries = vec![];
+        save_state(self, &mut entries);
+        snapshot::to_document(&entries)
+    }
+
+    /// Restore the state saved with [`WidgetContainer::save_state`].
+    /// State for an id that doesn't exist in the tree is ignored.
+    pub fn restore_state(&mut self, state: &str) -> Result<(), snapshot::Error> {
+        for (id, state) in snapshot::from_document(state)? {
+            if let Some(widget) = self.by_id(&NodeId::Value(id)) {
+                widget.inner.restore_state(state);
+            }
+        }
+        Ok(())
+    }
+
     pub fn stringify(&mut self) -> String {
         to_string(self, 0)
     }
@@ -359,15 +440,15 @@ impl WidgetContainer {
             return;
         }
 
-        if attributes.has(fields::BACKGROUND) {
-            self.background = attributes.background();
-        }
-
         if attributes.has(fields::DISPLAY) {
             self.display = attributes.display();
         }
 
         attributes.inner.retain(|k, v| {
+            if let Some(color) = v.to_color() {
+                return !self.animation.update_color_dst(k, color);
+            }
+
             let value = match v.to_signed_int() {
                 Some(val) => val as f32,
                 None => return true,
@@ -376,6 +457,14 @@ impl WidgetContainer {
             !self.animation.update_dst(k, value)
         });
 
+        if attributes.has(fields::BACKGROUND) {
+            self.background = attributes.background();
+        }
+
+        if attributes.has(fields::OPACITY) {
+            self.opacity = attributes.opacity();
+        }
+
         // Padding
         if let Some(left) = attributes.padding_left() {
             self.padding.left = left;
@@ -394,6 +483,19 @@ impl WidgetContainer {
     }
 }
 
+fn save_state(node: &mut WidgetContainer, entries: &mut Vec<(Value, Value)>) {
+    // Generated ids are different every time the widgets are created
+    if let NodeId::Value(id) = &node.id {
+        if let Some(state) = node.inner.save_state() {
+            entries.push((id.clone(), state));
+        }
+    }
+
+    for child in node.inner.children() {
+        save_state(child, entries);
+    }
+}
+
 fn to_string(node: &mut WidgetContainer, level: usize) -> String {
     let padding = " ".repeat(level * 4);
     let mut string = format!("{padding}{} {}\n", node.kind(), node.id);
@@ -405,3 +507,74 @@ fn to_string(node: &mut WidgetContainer, level: usize) -> String {
 
     string
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::widgets::{Number, VStack};
+
+    #[derive(Debug, Default)]
+    struct Scroll {
+        offset: u64,
+    }
+
+    impl Widget for Scroll {
+        fn kind(&self) -> &'static str {
+            "Scroll"
+        }
+
+        fn as_any(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+
+        fn layout(&mut self, _: LayoutCtx) -> Size {
+            Size::ZERO
+        }
+
+        fn position(&mut self, _: PositionCtx) {}
+
+        fn paint(&mut self, _: PaintCtx<'_, WithSize>) {}
+
+        fn children(&mut self) -> Vec<&mut WidgetContainer> {
+            vec![]
+        }
+
+        fn add_child(&mut self, _: WidgetContainer) {}
+
+        fn remove_child(&mut self, _: &NodeId) -> Option<WidgetContainer> {
+            None
+        }
+
+        fn update(&mut self, _: Attributes) {}
+
+        fn save_state(&self) -> Option<Value> {
+            Some(self.offset.into())
+        }
+
+        fn restore_state(&mut self, state: Value) {
+            if let Value::Number(Number::Unsigned(offset)) = state {
+                self.offset = offset;
+            }
+        }
+    }
+
+    fn tree(offsets: [u64; 2]) -> WidgetContainer {
+        let mut root = VStack::new(None, None).into_container(NodeId::auto());
+        root.add_child(Scroll { offset: offsets[0] }.into_container(NodeId::Value("list".into())));
+        root.add_child(Scroll { offset: offsets[1] }.into_container(NodeId::auto()));
+        root
+    }
+
+    #[test]
+    fn save_and_restore_state() {
+        let state = tree([3, 4]).save_state();
+        assert_eq!(state, "\"list\" = uint(3)\n");
+
+        let mut root = tree([0, 0]);
+        root.restore_state(&state).unwrap();
+        root.restore_state("\"missing\" = uint(1)").unwrap();
+        assert!(root.restore_state("\"list\" = ").is_err());
+
+        assert_eq!(root.by_id("list").unwrap().to::<Scroll>().offset, 3);
+    }
+}
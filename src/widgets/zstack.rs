@@ -69,6 +69,10 @@ impl Widget for ZStack {
         self
     }
 
+    fn needs_paint(&self) -> bool {
+        false
+    }
+
     fn layout(&mut self, mut ctx: LayoutCtx) -> Size {
         if let Some(min_width) = self.min_width {
             ctx.constraints.min_width = ctx.constraints.min_width.max(min_width);